@@ -1,7 +1,10 @@
 use web_sys::HtmlCanvasElement;
 
 pub trait CanvasProvider {
+    // The drawing buffer, in device pixels.
     fn set_size(&self, width: u32, height: u32);
+    // The size on the page, in CSS pixels.
+    fn set_display_size(&self, width: f64, height: f64);
 }
 
 impl CanvasProvider for HtmlCanvasElement {
@@ -9,6 +12,12 @@ impl CanvasProvider for HtmlCanvasElement {
         self.set_width(width);
         self.set_height(height);
     }
+
+    fn set_display_size(&self, width: f64, height: f64) {
+        let style = self.style();
+        style.set_property("width", &format!("{}px", width)).unwrap();
+        style.set_property("height", &format!("{}px", height)).unwrap();
+    }
 }
 
 #[cfg(test)]
@@ -16,5 +25,6 @@ mockall::mock! {
     pub CanvasProvider {}
     impl CanvasProvider for CanvasProvider {
         fn set_size(&self, width: u32, height: u32);
+        fn set_display_size(&self, width: f64, height: f64);
     }
 }
\ No newline at end of file
@@ -6,13 +6,14 @@ use web_sys::Window;
 
 pub type ClosureHandle = i32;
 
+type SharedClosure = Rc<RefCell<Closure<dyn FnMut(f64)>>>;
+
 pub trait ClosureWrapper: Clone {
     fn create(&mut self, callback: Box<dyn FnMut(f64)>);
 }
 
 pub trait FrameScheduler<CW: ClosureWrapper> {
     fn request_frame(&self, callback: &CW) -> ClosureHandle;
-    fn request_frame_after(&self, callback: &CW, timeout: i32);
     fn cancel(&self, handle: ClosureHandle);
 }
 
@@ -36,8 +37,9 @@ impl ClosureWrapper for WasmClosureWrapper {
     }
 }
 
+#[derive(Default)]
 pub struct WasmClosureWrapper {
-    pub closure: Option<Rc<RefCell<Closure<dyn FnMut(f64)>>>>
+    pub closure: Option<SharedClosure>
 }
 
 impl WasmClosureWrapper {
@@ -62,20 +64,6 @@ impl FrameScheduler<WasmClosureWrapper> for WebFrameScheduler {
         self.window.request_animation_frame(js_function).unwrap()
     }
 
-    fn request_frame_after(&self, handler: &WasmClosureWrapper, timeout: i32) {
-        
-        let window = self.window.clone();
-        let request_frame_handler = handler.clone();
-        let timeout_closure = Closure::wrap(Box::new(move || {
-            let request_frame_handler = request_frame_handler.closure.as_ref().unwrap().borrow();
-            let js_function = request_frame_handler.as_ref().unchecked_ref();
-            window.request_animation_frame(js_function).unwrap();
-        }) as Box<dyn FnMut()>);
-
-        self.window.set_timeout_with_callback_and_timeout_and_arguments_0(timeout_closure.as_ref().unchecked_ref(), timeout).unwrap();
-        timeout_closure.forget();
-    }
-    
     fn cancel(&self, handle: ClosureHandle) {
         self.window.cancel_animation_frame(handle).unwrap();
     }
@@ -100,7 +88,6 @@ mockall::mock! {
     pub FrameScheduler {}
     impl FrameScheduler<MockClosureWrapper> for FrameScheduler {
         fn request_frame(&self, callback: &MockClosureWrapper) -> ClosureHandle;
-        fn request_frame_after(&self, callback: &MockClosureWrapper, timeout: i32);
         fn cancel(&self, handle: ClosureHandle);
     }
 }
\ No newline at end of file
@@ -1,13 +1,18 @@
 use js_sys::Function;
 use wasm_bindgen::JsValue;
 
+use crate::{error::GameError, events::GameEvent, utils::to_js_value};
+
 pub trait InvokeJs  {
-    fn invoke(&self);
+    fn invoke(&self, event: &GameEvent) -> Result<(), GameError>;
 }
 
 impl InvokeJs for Function {
-    fn invoke(&self) {
-        self.call0(&JsValue::null()).unwrap();
+    fn invoke(&self, event: &GameEvent) -> Result<(), GameError> {
+        let payload = to_js_value(event).map_err(|error| GameError::CallbackFailed(error.to_string()))?;
+        self.call1(&JsValue::null(), &payload).map_err(|error| GameError::CallbackFailed(format!("{:?}", error)))?;
+
+        Ok(())
     }
 }
 
@@ -15,6 +20,6 @@ impl InvokeJs for Function {
 mockall::mock! {
     pub InvokeJsStub {}
     impl InvokeJs for InvokeJsStub {
-        fn invoke(&self);
+        fn invoke(&self, event: &GameEvent) -> Result<(), GameError>;
     }
-}
\ No newline at end of file
+}
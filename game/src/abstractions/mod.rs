@@ -1,15 +1,23 @@
-pub mod frame_scheduler;
-pub mod renderer;
 pub mod ai_controller;
-pub mod window_provider;
-pub mod canvas_provider;
-pub mod document_provider;
-pub mod invoke_js;
+pub mod transport;
 
-pub use renderer::{Renderer, WebGl2Renderer};
-pub use frame_scheduler::{FrameScheduler, ClosureWrapper, ClosureHandle};
-pub use ai_controller::{AiController, GreedyBfsAi};
-pub use window_provider::WindowProvider;
-pub use canvas_provider::CanvasProvider;
-pub use document_provider::DocumentProvider;
-pub use invoke_js::InvokeJs;
\ No newline at end of file
+pub use ai_controller::AiController;
+pub use transport::Transport;
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "wasm")] {
+        pub mod frame_scheduler;
+        pub mod renderer;
+        pub mod window_provider;
+        pub mod canvas_provider;
+        pub mod document_provider;
+        pub mod invoke_js;
+
+        pub use renderer::{Renderer, WebGl2Renderer};
+        pub use frame_scheduler::{FrameScheduler, ClosureWrapper, ClosureHandle};
+        pub use window_provider::WindowProvider;
+        pub use canvas_provider::CanvasProvider;
+        pub use document_provider::DocumentProvider;
+        pub use invoke_js::InvokeJs;
+    }
+}
@@ -1,73 +1,136 @@
 use js_sys::Float32Array;
-use web_sys::WebGl2RenderingContext;
+use web_sys::{WebGl2RenderingContext, WebGlUniformLocation};
 
-use crate::{constants::{FS_SOURCE, VS_SOURCE}, models::VerticePayload, utils::{create_program, create_shader}};
+use crate::{constants::{CELL_SPACING, FS_SOURCE, VS_SOURCE}, error::GameError, models::{CellInstance, CellKind, GridSize}, utils::{create_program, create_shader}};
+
+// Position, color and kind.
+const INSTANCE_FLOATS: usize = 2 + 4 + 1;
+
+// The unit square every cell is drawn from, as a triangle strip.
+const QUAD_CORNERS: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
 
 pub trait Renderer {
-    fn setup(&self);
-    fn set_viewport(&self, width: i32, height: i32);
-    fn draw(&mut self, vertices: &VerticePayload);
+    fn setup(&mut self) -> Result<(), GameError>;
+    // The part of the drawing buffer the grid is drawn into, from the bottom left corner.
+    fn set_viewport(&self, x: i32, y: i32, width: i32, height: i32);
+    fn set_grid(&self, grid: GridSize);
+    fn draw(&mut self, instances: &[CellInstance]);
 }
 
 pub struct WebGl2Renderer {
     context: WebGl2RenderingContext,
-    last_buffer_size: i32
+    grid_location: Option<WebGlUniformLocation>,
+    // Instances packed for upload, kept between frames so drawing does not allocate.
+    instance_data: Vec<f32>,
+    // Bytes allocated for the instance buffer. It only grows, so a growing snake does not reallocate it every tick.
+    buffer_capacity: i32,
 }
 
 impl Renderer for WebGl2Renderer {
 
-    fn setup(&self) {
+    fn setup(&mut self) -> Result<(), GameError> {
 
         let context = &self.context;
-        let vertex_shader = create_shader(&context, WebGl2RenderingContext::VERTEX_SHADER, VS_SOURCE).unwrap();
-        let fragment_shader = create_shader(&context, WebGl2RenderingContext::FRAGMENT_SHADER, FS_SOURCE).unwrap();
-        let program = create_program(&context, vertex_shader, fragment_shader).unwrap();
-    
+        let vertex_shader = create_shader(context, WebGl2RenderingContext::VERTEX_SHADER, VS_SOURCE)?;
+        let fragment_shader = create_shader(context, WebGl2RenderingContext::FRAGMENT_SHADER, FS_SOURCE)?;
+        let program = create_program(context, vertex_shader, fragment_shader)?;
+
         context.use_program(Some(&program));
-        
-        let position_buffer = context.create_buffer();
-        context.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, position_buffer.as_ref());
-    
-        let position_location = context.get_attrib_location(&program, "a_position") as u32;
-        let stride = 6 * 4;
-    
-        context.enable_vertex_attrib_array(position_location);
-        context.vertex_attrib_pointer_with_i32(position_location, 2, WebGl2RenderingContext::FLOAT, false, stride, 0);
-    
-        let color_location = context.get_attrib_location(&program, "a_color") as u32;
-        context.vertex_attrib_pointer_with_i32(color_location, 4, WebGl2RenderingContext::FLOAT, false, stride, 2 * 4);
-        context.enable_vertex_attrib_array(color_location);
+
+        let quad_buffer = context.create_buffer();
+        context.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, quad_buffer.as_ref());
+        let corners = unsafe { Float32Array::view(&QUAD_CORNERS) };
+        context.buffer_data_with_array_buffer_view(WebGl2RenderingContext::ARRAY_BUFFER, &corners, WebGl2RenderingContext::STATIC_DRAW);
+
+        let corner_location = context.get_attrib_location(&program, "a_corner") as u32;
+        context.enable_vertex_attrib_array(corner_location);
+        context.vertex_attrib_pointer_with_i32(corner_location, 2, WebGl2RenderingContext::FLOAT, false, 0, 0);
+
+        // The instance buffer stays bound, `draw` uploads into it.
+        let instance_buffer = context.create_buffer();
+        context.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, instance_buffer.as_ref());
+
+        let stride = (INSTANCE_FLOATS * 4) as i32;
+        let attributes = [("a_cell", 2, 0), ("a_color", 4, 2), ("a_kind", 1, 6)];
+
+        for (name, size, offset) in attributes {
+            let location = context.get_attrib_location(&program, name) as u32;
+            context.enable_vertex_attrib_array(location);
+            context.vertex_attrib_pointer_with_i32(location, size, WebGl2RenderingContext::FLOAT, false, stride, offset * 4);
+            context.vertex_attrib_divisor(location, 1);
+        }
+
+        context.uniform1f(context.get_uniform_location(&program, "u_spacing").as_ref(), CELL_SPACING);
+        self.grid_location = context.get_uniform_location(&program, "u_grid");
+
+        Ok(())
+    }
+
+    fn set_viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.context.viewport(x, y, width, height);
     }
 
-    fn set_viewport(&self, _width: i32, _height: i32) {
-        // self.context.viewport(0, 0, width, height);
-        let width = self.context.drawing_buffer_width();
-        let height = self.context.drawing_buffer_height();
-        self.context.viewport(0, 0, width, height);
+    fn set_grid(&self, grid: GridSize) {
+        self.context.uniform2f(self.grid_location.as_ref(), grid.width as f32, grid.height as f32);
     }
 
-    fn draw(&mut self, payload: &VerticePayload) {
-        let buffer_size = (payload.length * 4) as i32;
-        let vertices = unsafe { Float32Array::view(&payload.data) };
-        
-        if self.last_buffer_size != buffer_size {
-            self.context.buffer_data_with_i32(WebGl2RenderingContext::ARRAY_BUFFER, buffer_size, WebGl2RenderingContext::DYNAMIC_DRAW);
-            self.last_buffer_size = buffer_size;
+    fn draw(&mut self, instances: &[CellInstance]) {
+        pack_instances(instances, &mut self.instance_data);
+
+        let buffer_size = (self.instance_data.len() * 4) as i32;
+        let capacity = buffer_capacity(self.buffer_capacity, buffer_size);
+
+        if capacity != self.buffer_capacity {
+            self.buffer_capacity = capacity;
+            self.context.buffer_data_with_i32(WebGl2RenderingContext::ARRAY_BUFFER, self.buffer_capacity, WebGl2RenderingContext::DYNAMIC_DRAW);
         }
 
+        let data = unsafe { Float32Array::view(&self.instance_data) };
+
         self.context.buffer_sub_data_with_i32_and_array_buffer_view(
             WebGl2RenderingContext::ARRAY_BUFFER,
             0,
-            &vertices,
+            &data,
         );
-        
-        self.context.draw_arrays(WebGl2RenderingContext::TRIANGLES, 0, payload.vertice_size);
+
+        self.context.draw_arrays_instanced(WebGl2RenderingContext::TRIANGLE_STRIP, 0, 4, instances.len() as i32);
+    }
+}
+
+// Replaces `data` with the instances laid out as the attributes in `setup` read them,
+// `INSTANCE_FLOATS` per cell.
+fn pack_instances(instances: &[CellInstance], data: &mut Vec<f32>) {
+    data.clear();
+
+    for instance in instances {
+        let (x, y) = instance.position;
+        let [r, g, b, a] = instance.color;
+        data.extend_from_slice(&[x, y, r, g, b, a, kind_value(instance.kind)]);
+    }
+}
+
+// Bytes the instance buffer needs to hold `size` bytes. It grows to the next power of two and never shrinks.
+fn buffer_capacity(capacity: i32, size: i32) -> i32 {
+    if size > capacity { (size as u32).next_power_of_two() as i32 } else { capacity }
+}
+
+// The shader's `a_kind`.
+fn kind_value(kind: CellKind) -> f32 {
+    match kind {
+        CellKind::Snake => 0.0,
+        CellKind::Food => 1.0,
+        CellKind::Obstacle => 2.0,
     }
 }
 
 impl WebGl2Renderer {
     pub fn new(context: WebGl2RenderingContext) -> Self {
-        WebGl2Renderer { context, last_buffer_size: 0 }
+        WebGl2Renderer {
+            context,
+            grid_location: None,
+            instance_data: vec![],
+            buffer_capacity: 0,
+        }
     }
 }
 
@@ -75,8 +138,39 @@ impl WebGl2Renderer {
 mockall::mock! {
     pub Renderer {}
     impl Renderer for Renderer {
-        fn setup(&self);
-        fn set_viewport(&self, width: i32, height: i32);
-        fn draw(&mut self, vertices: &VerticePayload);
+        fn setup(&mut self) -> Result<(), GameError>;
+        fn set_viewport(&self, x: i32, y: i32, width: i32, height: i32);
+        fn set_grid(&self, grid: GridSize);
+        fn draw(&mut self, instances: &[CellInstance]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_each_instance_as_the_shader_reads_it() {
+        let instances = [
+            CellInstance { position: (1.5, 2.0), color: [0.1, 0.2, 0.3, 1.0], kind: CellKind::Snake },
+            CellInstance { position: (3.0, 4.0), color: [1.0, 0.0, 0.0, 0.5], kind: CellKind::Food },
+            CellInstance { position: (5.0, 6.0), color: [0.7, 0.7, 0.7, 1.0], kind: CellKind::Obstacle },
+        ];
+        let mut data = vec![9.0; 50];
+
+        pack_instances(&instances, &mut data);
+
+        assert_eq!(data.len(), instances.len() * INSTANCE_FLOATS, "Last frame's data should be replaced");
+        assert_eq!(data[..INSTANCE_FLOATS], [1.5, 2.0, 0.1, 0.2, 0.3, 1.0, 0.0]);
+        assert_eq!(data[INSTANCE_FLOATS..2 * INSTANCE_FLOATS], [3.0, 4.0, 1.0, 0.0, 0.0, 0.5, 1.0]);
+        assert_eq!(data[2 * INSTANCE_FLOATS..], [5.0, 6.0, 0.7, 0.7, 0.7, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn instance_buffer_only_grows() {
+        assert_eq!(buffer_capacity(0, 84), 128);
+        assert_eq!(buffer_capacity(128, 112), 128, "A buffer that fits should be kept");
+        assert_eq!(buffer_capacity(128, 140), 256);
+        assert_eq!(buffer_capacity(256, 28), 256, "A shorter snake should not shrink the buffer");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,19 @@
+use crate::error::GameError;
+
+// One end of a connection, sending `S` messages and receiving `R` messages.
+pub trait Transport<S, R> {
+    fn send(&mut self, message: S) -> Result<(), GameError>;
+
+    // Returns the next message that has arrived, without waiting for one.
+    fn receive(&mut self) -> Option<R>;
+}
+
+impl<S, R, T: Transport<S, R> + ?Sized> Transport<S, R> for Box<T> {
+    fn send(&mut self, message: S) -> Result<(), GameError> {
+        (**self).send(message)
+    }
+
+    fn receive(&mut self) -> Option<R> {
+        (**self).receive()
+    }
+}
@@ -4,6 +4,7 @@ use web_sys::Window;
 pub trait WindowProvider {
     fn get_inner_width(&self) -> f64;
     fn get_inner_height(&self) -> f64;
+    fn get_device_pixel_ratio(&self) -> f64;
     fn on_resize(&self, handler: Box<dyn FnMut()>);
 }
 
@@ -17,6 +18,10 @@ impl WindowProvider for Window {
         self.inner_height().unwrap().as_f64().unwrap()
     }
 
+    fn get_device_pixel_ratio(&self) -> f64 {
+        self.device_pixel_ratio()
+    }
+
     fn on_resize(&self, handler: Box<dyn FnMut()>) {
         let closure = Closure::new(handler);
         self.add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref()).unwrap();
@@ -30,6 +35,7 @@ mockall::mock! {
     impl WindowProvider for WindowProvider {
         fn get_inner_width(&self) -> f64;
         fn get_inner_height(&self) -> f64;
+        fn get_device_pixel_ratio(&self) -> f64;
         fn on_resize(&self, handler: Box<dyn FnMut()>);
     }
 }
\ No newline at end of file
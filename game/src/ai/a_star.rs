@@ -0,0 +1,108 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}};
+
+use crate::{abstractions::AiController, models::{BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::{direction_between, Board};
+
+#[derive(Default)]
+pub struct AStarAi;
+
+impl AiController for AStarAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        let board = Board::new(snake, obstacles, grid, boundary_mode);
+        let head = snake.get_head_position();
+
+        // Manhattan distance to the nearest food, measured across wrapping edges.
+        let heuristic = |position: (i32, i32)| foods.iter()
+            .map(|food| boundary_mode.distance(position, food.position, grid))
+            .min();
+
+        let mut open = BinaryHeap::from([Reverse((heuristic(head)?, 0, head))]);
+        let mut costs = HashMap::from([(head, 0)]);
+        let mut parents = HashMap::new();
+
+        while let Some(Reverse((_, cost, current))) = open.pop() {
+            if current != head && foods.iter().any(|food| food.position == current) {
+                let mut step = current;
+
+                while let Some(&parent) = parents.get(&step) {
+                    if parent == head {
+                        break;
+                    }
+                    step = parent;
+                }
+
+                return Some(direction_between(head, step));
+            }
+
+            if costs.get(&current).is_some_and(|&known| known < cost) {
+                continue;
+            }
+
+            let next_cost = cost + 1;
+
+            // The cost of a cell is also the move on which the head reaches it.
+            for neighbour in board.neighbours_at(current, next_cost as usize) {
+                if costs.get(&neighbour).is_none_or(|&known| next_cost < known) {
+                    costs.insert(neighbour, next_cost);
+                    parents.insert(neighbour, current);
+                    open.push(Reverse((next_cost + heuristic(neighbour).unwrap_or_default(), next_cost, neighbour)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl AStarAi {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::snake_at;
+
+    fn food_at(pos: (i32, i32)) -> Food {
+        Food::new([1.0, 0.0, 0.0, 1.0], pos)
+    }
+
+    #[test]
+    fn routes_around_wall() {
+        let ai = AStarAi::new();
+        let snake = snake_at((3, 5), 3);
+        let wall = Obstacle::from_cells([0.5; 4], (3..10).map(|y| (4, y)));
+
+        let direction = ai.get_direction(&snake, &[food_at((6, 5))], &[wall], GridSize::square(10), BoundaryMode::Solid);
+
+        assert_eq!(direction, Some(Direction::Down), "Only the gap below the wall leads to the food");
+    }
+
+    #[test]
+    fn takes_wrap_around_shortcut() {
+        let ai = AStarAi::new();
+        let snake = snake_at((8, 5), 3);
+        let direction = ai.get_direction(&snake, &[food_at((1, 5))], &[], GridSize::square(10), BoundaryMode::Wrap);
+        assert_eq!(direction, Some(Direction::Right));
+    }
+
+    #[test]
+    fn returns_none_without_reachable_food() {
+        let ai = AStarAi::new();
+        let snake = snake_at((2, 2), 3);
+        let walls = Obstacle::from_cells([0.5; 4], [(5, 4), (4, 5), (6, 5), (5, 6)]);
+
+        assert_eq!(ai.get_direction(&snake, &[food_at((5, 5))], &[walls], GridSize::square(10), BoundaryMode::Solid), None);
+        assert_eq!(ai.get_direction(&snake, &[], &[], GridSize::square(10), BoundaryMode::Solid), None);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{abstractions::AiController, models::{AiStrategy, BoundaryMode, Direction, FoodKind, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::Board;
+
+// Keeps the score from counting for much when food is far away.
+const DISTANCE_BIAS: f64 = 2.0;
+
+// Sits in front of a strategy and decides which food is worth going for. Poison is walled off,
+// golden food that would expire on the way is ignored and, when the kinds are worth different
+// amounts, the strategy only sees the food with the best points for the distance.
+pub struct FoodAwareAi {
+    controller: Box<dyn AiController>,
+}
+
+impl FoodAwareAi {
+    pub fn new(controller: Box<dyn AiController>) -> Self {
+        Self { controller }
+    }
+
+    fn targets(&self, snake: &Snake, foods: &[Food], obstacles: &[Obstacle], grid: GridSize, boundary_mode: BoundaryMode) -> Vec<Food> {
+        let board = Board::new(snake, obstacles, grid, boundary_mode);
+        let head = snake.get_head_position();
+
+        let reachable: Vec<_> = foods.iter()
+            .filter(|food| food.kind != FoodKind::Poison)
+            .filter_map(|food| {
+                let distance = board.shortest_path(head, |position| position == food.position)?.len();
+                let in_time = food.lifetime.is_none_or(|lifetime| distance as u32 <= lifetime);
+                in_time.then_some((food, distance))
+            })
+            .collect();
+
+        let points = reachable.first().map(|(food, _)| food.kind.points());
+
+        if reachable.iter().all(|(food, _)| Some(food.kind.points()) == points) {
+            return reachable.into_iter().map(|(food, _)| food.clone()).collect();
+        }
+
+        let value = |&(food, distance): &(&Food, usize)| food.kind.points() as f64 / (distance as f64 + DISTANCE_BIAS);
+
+        reachable.iter()
+            .max_by(|a, b| value(a).total_cmp(&value(b)))
+            .map(|(food, _)| (*food).clone())
+            .into_iter()
+            .collect()
+    }
+}
+
+impl AiController for FoodAwareAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        // Plain food needs no weighing, so the strategy sees the board exactly as it is.
+        if foods.iter().all(|food| food.kind == FoodKind::Regular) {
+            return self.controller.get_direction(snake, foods, obstacles, grid, boundary_mode);
+        }
+
+        let poison = foods.iter().filter(|food| food.kind == FoodKind::Poison).map(|food| food.position);
+        let mut obstacles = obstacles.to_vec();
+        obstacles.push(Obstacle::from_cells([0.0; 4], poison));
+
+        let targets = self.targets(snake, foods, &obstacles, grid, boundary_mode);
+        self.controller.get_direction(snake, &targets, &obstacles, grid, boundary_mode)
+    }
+
+    fn set_strategy(&mut self, strategy: AiStrategy) {
+        self.controller.set_strategy(strategy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::snake_at;
+    use crate::ai::GreedyBfsAi;
+
+    fn food(kind: FoodKind, position: (i32, i32)) -> Food {
+        Food::of_kind(kind, [1.0; 4], position)
+    }
+
+    fn ai() -> FoodAwareAi {
+        FoodAwareAi::new(Box::new(GreedyBfsAi::new()))
+    }
+
+    #[test]
+    fn steers_around_poison() {
+        let snake = snake_at((5, 5), 3);
+        let foods = [food(FoodKind::Poison, (6, 5)), food(FoodKind::Regular, (8, 5))];
+
+        let direction = ai().get_direction(&snake, &foods, &[], GridSize::square(10), BoundaryMode::Solid);
+
+        assert!(direction.is_some());
+        assert_ne!(direction, Some(Direction::Right), "The poison is straight ahead");
+    }
+
+    #[test]
+    fn prefers_food_worth_more() {
+        let snake = snake_at((5, 5), 3);
+        let foods = [food(FoodKind::Regular, (5, 3)), food(FoodKind::Bonus, (7, 5))];
+
+        assert_eq!(GreedyBfsAi::new().get_direction(&snake, &foods, &[], GridSize::square(10), BoundaryMode::Solid), Some(Direction::Down));
+        assert_eq!(ai().get_direction(&snake, &foods, &[], GridSize::square(10), BoundaryMode::Solid), Some(Direction::Right));
+    }
+
+    #[test]
+    fn ignores_golden_food_it_cannot_reach_in_time() {
+        let snake = snake_at((2, 2), 3);
+        let mut golden = food(FoodKind::Golden, (9, 2));
+        golden.lifetime = Some(3);
+        let foods = [food(FoodKind::Regular, (2, 0)), golden];
+
+        assert_eq!(ai().get_direction(&snake, &foods, &[], GridSize::square(10), BoundaryMode::Solid), Some(Direction::Down));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{abstractions::AiController, models::{BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::{direction_between, Board};
+
+#[derive(Default)]
+pub struct GreedyBfsAi;
+
+impl AiController for GreedyBfsAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        if let Some(target_position) = self.find_closest_food(snake, foods, grid, boundary_mode) {
+            let snake_head = snake.get_head_position();
+            let board = Board::new(snake, obstacles, grid, boundary_mode);
+            let path = board.shortest_path(snake_head, |position| position == target_position);
+
+            if let Some(next_step) = path.and_then(|path| path.first().copied()) {
+                return Some(direction_between(snake_head, next_step));
+            }
+        }
+        None
+    }
+}
+
+impl GreedyBfsAi {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn find_closest_food(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<(i32, i32)> {
+        let head = snake.get_head_position();
+        foods.iter()
+            .min_by_key(|food| boundary_mode.distance(head, food.position, grid))
+            .map(|food| food.position)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{coiled_snake, snake_at};
+    use crate::objects::{Food, Obstacle};
+    use crate::models::{BoundaryMode, Direction};
+
+    fn food_at(pos: (i32, i32)) -> Food {
+        Food::new([1.0, 0.0, 0.0, 1.0], pos)
+    }
+
+    fn obstacle_at(pos: (i32, i32)) -> Obstacle {
+        Obstacle::new([0.5, 0.5, 0.5, 1.0], pos)
+    }
+
+    #[test]
+    fn moves_toward_food() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((5, 5), 3);
+        let food = vec![food_at((7, 5))];
+        let direction = ai.get_direction(&snake, &food, &[], GridSize::square(10), BoundaryMode::Wrap);
+        assert_eq!(direction, Some(Direction::Right));
+    }
+
+    #[test]
+    fn avoids_single_obstacle() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((5, 5), 3);
+        let food = vec![food_at((7, 5))];
+        let obstacles = vec![obstacle_at((6, 5))];
+        let direction = ai.get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Wrap);
+        assert_ne!(direction, Some(Direction::Right));
+    }
+
+    #[test]
+    fn trapped_snake_returns_none() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((5, 5), 3);
+        let food = vec![food_at((6, 5))];
+        let obstacles = vec![
+            obstacle_at((6, 5)),
+            obstacle_at((4, 5)),
+            obstacle_at((5, 6)),
+            obstacle_at((5, 4)),
+        ];
+        let direction = ai.get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Wrap);
+        assert_eq!(direction, None);
+    }
+
+    #[test]
+    fn picks_available_direction() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((3, 3), 3);
+        let food = vec![food_at((0, 0))];
+        let obstacles = vec![obstacle_at((2, 3))];
+        let direction = ai.get_direction(&snake, &food, &obstacles, GridSize::square(5), BoundaryMode::Solid);
+        assert!(direction == Some(Direction::Up) || direction == Some(Direction::Down));
+    }
+
+    #[test]
+    fn takes_wrap_around_shortcut() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((8, 5), 3);
+        let food = vec![food_at((1, 5))];
+        let direction = ai.get_direction(&snake, &food, &[], GridSize::square(10), BoundaryMode::Wrap);
+        assert_eq!(direction, Some(Direction::Right));
+    }
+
+    #[test]
+    fn paths_through_cells_the_tail_leaves() {
+        let ai = GreedyBfsAi::new();
+        let snake = coiled_snake();
+        let food = vec![food_at((3, 3))];
+        let direction = ai.get_direction(&snake, &food, &[], GridSize::square(10), BoundaryMode::Solid);
+        assert_eq!(direction, Some(Direction::Up), "Tail at (3, 2) will have moved by the time the head gets there");
+    }
+
+    #[test]
+    fn does_not_cross_solid_edge() {
+        let ai = GreedyBfsAi::new();
+        let snake = snake_at((8, 5), 3);
+        let food = vec![food_at((1, 5))];
+        let direction = ai.get_direction(&snake, &food, &[], GridSize::square(10), BoundaryMode::Solid);
+        assert!(direction.is_some());
+        assert_ne!(direction, Some(Direction::Right), "The food is only three steps away across the edge");
+    }
+}
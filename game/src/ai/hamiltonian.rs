@@ -0,0 +1,135 @@
+use crate::{abstractions::AiController, models::{BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::{direction_between, Board, TailChasingAi};
+
+// Cells kept between the head and the body ahead of it when cutting across the cycle,
+// so a snake that grows while taking a shortcut still cannot catch up with itself.
+const SHORTCUT_MARGIN: i32 = 4;
+
+// Follows a fixed cycle through every cell of the grid, which can never run into the body.
+// Shortcuts are only taken while the snake is short enough for them to be safe.
+#[derive(Default)]
+pub struct HamiltonianAi {
+    fallback: TailChasingAi,
+}
+
+impl AiController for HamiltonianAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        // A closed tour of the whole grid only exists when it has an even number of cells.
+        if grid.width < 2 || grid.height < 2 || grid.area() % 2 != 0 {
+            return self.fallback.get_direction(snake, foods, obstacles, grid, boundary_mode);
+        }
+
+        let mut board = Board::new(snake, obstacles, grid, boundary_mode);
+        board.unblock(snake.get_tail_position());
+
+        let cells = grid.area() as i32;
+        let head = snake.get_head_position();
+        let head_index = cycle_index(head, grid);
+        let distance = |position: (i32, i32)| (cycle_index(position, grid) - head_index).rem_euclid(cells);
+
+        // Growing extends the tail outwards rather than along the cycle,
+        // so look for the nearest body cell ahead instead of trusting the tail.
+        let body_distance = snake.body().iter().skip(1).map(|&segment| distance(segment)).min().unwrap_or(cells);
+        let food_distance = foods.iter().map(|food| distance(food.position)).min();
+
+        if let Some(food_distance) = food_distance.filter(|_| (snake.length() as i32) < cells / 2) {
+            let shortcut = board.neighbours(head)
+                .filter(|&next| distance(next) <= food_distance && distance(next) + SHORTCUT_MARGIN < body_distance)
+                .max_by_key(|&next| distance(next));
+
+            if let Some(next) = shortcut {
+                return Some(direction_between(head, next));
+            }
+        }
+
+        match board.neighbours(head).find(|&next| distance(next) == 1) {
+            Some(next) => Some(direction_between(head, next)),
+            None => self.fallback.get_direction(snake, foods, obstacles, grid, boundary_mode),
+        }
+    }
+}
+
+impl HamiltonianAi {
+    pub fn new() -> Self {
+        Self {
+            fallback: TailChasingAi::new(),
+        }
+    }
+}
+
+// Position of a cell on the cycle: along the bottom row, up the grid in a zigzag
+// that skips the first column, then back down the first column to the start.
+fn cycle_index((x, y): (i32, i32), grid: GridSize) -> i32 {
+    // The zigzag only ends next to the first column with an even number of rows,
+    // so a grid with an odd number runs the same cycle on its side.
+    if grid.height % 2 != 0 {
+        return cycle_index((y, x), GridSize::new(grid.height, grid.width));
+    }
+
+    if y == 0 {
+        x
+    }
+    else if x == 0 {
+        grid.width * grid.height - y
+    }
+    else {
+        let offset = if y % 2 == 1 { grid.width - 1 - x } else { x - 1 };
+        grid.width + (y - 1) * (grid.width - 1) + offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Game, models::{AiStrategy, GameOptions, GameResult}, randomizer::SeededRandomizer};
+
+    #[test]
+    fn cycle_visits_every_cell_once() {
+        for grid in [GridSize::square(6), GridSize::new(7, 4), GridSize::new(4, 7)] {
+            let mut cells: Vec<_> = grid.cells().collect();
+            cells.sort_by_key(|&cell| cycle_index(cell, grid));
+
+            for (index, pair) in cells.windows(2).enumerate() {
+                assert_eq!(cycle_index(pair[0], grid), index as i32);
+                assert_eq!(BoundaryMode::Solid.distance(pair[0], pair[1], grid), 1, "{:?} should neighbour {:?} on {:?}", pair[0], pair[1], grid);
+            }
+
+            assert_eq!(BoundaryMode::Solid.distance(cells[cells.len() - 1], cells[0], grid), 1, "Cycle should close on {:?}", grid);
+        }
+    }
+
+    #[test]
+    fn survives_on_a_solid_board() {
+        let options = GameOptions {
+            grid_width: 12,
+            grid_height: 12,
+            seed: Some(7),
+            boundary_mode: BoundaryMode::Solid,
+            ai_strategy: AiStrategy::Hamiltonian,
+            ..GameOptions::test_default()
+        };
+        let mut game = Game::new(options, SeededRandomizer::new(7));
+        game.initialize().unwrap();
+        let ai = HamiltonianAi::new();
+
+        // Once the board is full there is nowhere left to go, which counts as winning.
+        while !game.foods.is_empty() {
+            if let Some(direction) = ai.get_direction(&game.players[0].snake, &game.foods, &game.obstacles, GridSize::square(12), BoundaryMode::Solid) {
+                game.change_direction(direction);
+            }
+
+            assert_ne!(game.update(), GameResult::Over, "Snake died at length {} after {} ticks", game.players[0].snake.length(), game.tick());
+            assert!(game.tick() < 10_000, "Snake should keep eating");
+        }
+
+        assert!(game.players[0].snake.length() >= 144, "Snake should fill the board");
+    }
+}
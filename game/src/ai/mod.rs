@@ -0,0 +1,270 @@
+mod greedy;
+mod a_star;
+mod hamiltonian;
+mod tail_chasing;
+mod survival;
+mod food_aware;
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+pub use greedy::GreedyBfsAi;
+pub use a_star::AStarAi;
+pub use hamiltonian::HamiltonianAi;
+pub use tail_chasing::TailChasingAi;
+pub use survival::SurvivalAi;
+pub use food_aware::FoodAwareAi;
+
+use crate::{abstractions::AiController, models::{AiStrategy, BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+// Every strategy is wrapped so it knows how to treat the different kinds of food.
+pub fn create_ai_controller(strategy: AiStrategy) -> Box<dyn AiController> {
+    let controller: Box<dyn AiController> = match strategy {
+        AiStrategy::Greedy => Box::new(GreedyBfsAi::new()),
+        AiStrategy::AStar => Box::new(AStarAi::new()),
+        AiStrategy::Hamiltonian => Box::new(HamiltonianAi::new()),
+        AiStrategy::TailChasing => Box::new(TailChasingAi::new()),
+        AiStrategy::Survival => Box::new(SurvivalAi::new()),
+    };
+
+    Box::new(FoodAwareAi::new(controller))
+}
+
+// Delegates to the controller for the selected strategy, which can be swapped while running.
+pub struct StrategyAi {
+    strategy: AiStrategy,
+    controller: Box<dyn AiController>,
+}
+
+impl StrategyAi {
+    pub fn new(strategy: AiStrategy) -> Self {
+        Self {
+            strategy,
+            controller: create_ai_controller(strategy),
+        }
+    }
+
+    pub fn strategy(&self) -> AiStrategy {
+        self.strategy
+    }
+}
+
+impl AiController for StrategyAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        self.controller.get_direction(snake, foods, obstacles, grid, boundary_mode)
+    }
+
+    fn set_strategy(&mut self, strategy: AiStrategy) {
+        if self.strategy != strategy {
+            *self = Self::new(strategy);
+        }
+    }
+}
+
+// A snapshot of which cells the snake cannot enter, shared by the search based strategies.
+// Body cells also remember how many moves it takes until the tail has passed them.
+#[derive(Clone)]
+pub(crate) struct Board {
+    grid: GridSize,
+    boundary_mode: BoundaryMode,
+    blocked: HashSet<(i32, i32)>,
+    body: HashMap<(i32, i32), usize>,
+}
+
+impl Board {
+    pub fn new(snake: &Snake, obstacles: &[Obstacle], grid: GridSize, boundary_mode: BoundaryMode) -> Self {
+        Self::with_body(snake.body(), obstacles, grid, boundary_mode)
+    }
+
+    pub fn with_body(body: &[(i32, i32)], obstacles: &[Obstacle], grid: GridSize, boundary_mode: BoundaryMode) -> Self {
+        let blocked = obstacles.iter().flat_map(Obstacle::cells).collect();
+        let mut cells = HashMap::new();
+
+        // Iterating from the tail lets segments nearer the head overwrite a grown tail sharing their cell.
+        for (index, &segment) in body.iter().enumerate().rev() {
+            cells.insert(segment, body.len() - index);
+        }
+
+        Self {
+            grid,
+            boundary_mode,
+            blocked,
+            body: cells,
+        }
+    }
+
+    pub fn is_free(&self, position: (i32, i32)) -> bool {
+        self.grid.contains(position) && !self.blocked.contains(&position) && !self.body.contains_key(&position)
+    }
+
+    // Whether the snake may enter `position` on its `step`-th move from now.
+    pub fn is_free_at(&self, position: (i32, i32), step: usize) -> bool {
+        self.grid.contains(position)
+            && !self.blocked.contains(&position)
+            && self.body.get(&position).is_none_or(|&free_after| step >= free_after)
+    }
+
+    pub fn block(&mut self, position: (i32, i32)) {
+        self.blocked.insert(position);
+    }
+
+    pub fn unblock(&mut self, position: (i32, i32)) {
+        self.blocked.remove(&position);
+        self.body.remove(&position);
+    }
+
+    pub fn neighbours(&self, position: (i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.adjacent(position).filter(|&neighbour| self.is_free(neighbour))
+    }
+
+    pub fn neighbours_at(&self, position: (i32, i32), step: usize) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.adjacent(position).filter(move |&neighbour| self.is_free_at(neighbour, step))
+    }
+
+    fn adjacent(&self, position: (i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
+        Direction::ALL.into_iter()
+            .filter_map(move |direction| {
+                let (dx, dy) = direction.offset();
+                self.boundary_mode.normalize((position.0 + dx, position.1 + dy), self.grid)
+            })
+    }
+
+    // Breadth-first path to the nearest cell accepted by `is_goal`, without the start cell.
+    // A body cell can be stepped on once the snake will have moved past it by the time it gets there.
+    pub fn shortest_path(&self, start: (i32, i32), is_goal: impl Fn((i32, i32)) -> bool) -> Option<Vec<(i32, i32)>> {
+        self.search(start, is_goal, true)
+    }
+
+    // Like `shortest_path`, but treats the whole body as solid for the entire search.
+    pub fn shortest_static_path(&self, start: (i32, i32), is_goal: impl Fn((i32, i32)) -> bool) -> Option<Vec<(i32, i32)>> {
+        self.search(start, is_goal, false)
+    }
+
+    fn search(&self, start: (i32, i32), is_goal: impl Fn((i32, i32)) -> bool, timed: bool) -> Option<Vec<(i32, i32)>> {
+        let mut parents = HashMap::new();
+        let mut queue = VecDeque::from([(start, 0)]);
+        parents.insert(start, start);
+
+        while let Some((current, step)) = queue.pop_front() {
+            if current != start && is_goal(current) {
+                let mut path = vec![current];
+                let mut position = current;
+
+                while let Some(&parent) = parents.get(&position) {
+                    if parent == start {
+                        break;
+                    }
+                    path.push(parent);
+                    position = parent;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            let step = if timed { step + 1 } else { 0 };
+
+            for neighbour in self.neighbours_at(current, step) {
+                if let Entry::Vacant(entry) = parents.entry(neighbour) {
+                    entry.insert(current);
+                    queue.push_back((neighbour, step));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Number of free cells reachable from `start`, counting `start` itself.
+    pub fn reachable_area(&self, start: (i32, i32)) -> usize {
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(current) = queue.pop_front() {
+            for neighbour in self.neighbours(current) {
+                if visited.insert(neighbour) {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        visited.len()
+    }
+}
+
+pub(crate) fn direction_between(start: (i32, i32), next: (i32, i32)) -> Direction {
+    // A step across a wrapping edge shows up as a jump of more than one cell.
+    let wrap = |delta: i32| match delta {
+        delta if delta > 1 => -1,
+        delta if delta < -1 => 1,
+        delta => delta,
+    };
+
+    match (wrap(next.0 - start.0), wrap(next.1 - start.1)) {
+        (1, 0) => Direction::Right,
+        (-1, 0) => Direction::Left,
+        (0, 1) => Direction::Up,
+        (0, -1) => Direction::Down,
+        _ => Direction::Up,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::coiled_snake;
+
+    #[test]
+    fn switches_strategy_at_runtime() {
+        let mut ai = StrategyAi::new(AiStrategy::Greedy);
+        ai.set_strategy(AiStrategy::Hamiltonian);
+        assert_eq!(ai.strategy(), AiStrategy::Hamiltonian);
+    }
+
+    #[test]
+    fn every_strategy_moves_toward_nearby_food() {
+        let mut snake = Snake::new();
+        snake.set_spawn((5, 5), Direction::Right, 3);
+        snake.initialize(3);
+        let foods = vec![Food::new([1.0, 0.0, 0.0, 1.0], (7, 5))];
+
+        for strategy in [AiStrategy::Greedy, AiStrategy::AStar, AiStrategy::TailChasing, AiStrategy::Survival] {
+            let ai = StrategyAi::new(strategy);
+            let direction = ai.get_direction(&snake, &foods, &[], GridSize::square(20), BoundaryMode::Solid);
+            assert_eq!(direction, Some(Direction::Right), "{:?} should head for the food", strategy);
+        }
+    }
+
+    #[test]
+    fn shortest_path_skips_blocked_cells() {
+        let mut snake = Snake::new();
+        snake.set_spawn((1, 1), Direction::Right, 2);
+        snake.initialize(2);
+        let obstacles = vec![Obstacle::from_cells([0.0; 4], [(2, 0), (2, 1), (2, 2)])];
+        let board = Board::new(&snake, &obstacles, GridSize::square(4), BoundaryMode::Solid);
+
+        let path = board.shortest_path((1, 1), |position| position == (3, 1)).unwrap();
+
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&(3, 1)));
+        assert_eq!(board.reachable_area((1, 1)), 12);
+    }
+
+    #[test]
+    fn shortest_path_uses_cells_the_tail_leaves() {
+        let snake = coiled_snake();
+        let board = Board::new(&snake, &[], GridSize::square(10), BoundaryMode::Solid);
+
+        let path = board.shortest_path((3, 1), |position| position == (3, 3)).unwrap();
+
+        assert_eq!(path, vec![(3, 2), (3, 3)], "The tail at (3, 2) moves away before the head arrives");
+        assert!(!board.is_free_at((2, 1), 1), "The neck is still there after one move");
+        assert!(board.is_free_at((2, 1), 3));
+    }
+}
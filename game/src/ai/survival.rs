@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use crate::{abstractions::AiController, models::{BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::{direction_between, Board};
+
+// Only follows a path to food when the snake could still reach its own tail after eating,
+// otherwise it stalls with the move that keeps the most room.
+#[derive(Default)]
+pub struct SurvivalAi;
+
+impl AiController for SurvivalAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        let board = Board::new(snake, obstacles, grid, boundary_mode);
+        let head = snake.get_head_position();
+
+        // Routes that squeeze in behind the tail tend to split the board, so the food search
+        // keeps clear of the whole body and only the escape check counts on the tail moving.
+        if let Some(path) = board.shortest_static_path(head, |position| foods.iter().any(|food| food.position == position)) {
+            let body = simulate(snake.body(), &path, true);
+
+            if is_tail_reachable(&body, obstacles, grid, boundary_mode) {
+                return Some(direction_between(head, path[0]));
+            }
+        }
+
+        self.longest_safe_move(snake, obstacles, grid, boundary_mode)
+    }
+}
+
+impl SurvivalAi {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    // Prefers moves after which the tail is still reachable, taking the longest way round to it
+    // so the snake buys time for the board to open up. Without one, picks the largest open area.
+    fn longest_safe_move(
+        &self,
+        snake: &Snake,
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        let head = snake.get_head_position();
+        let mut board = Board::new(snake, obstacles, grid, boundary_mode);
+
+        if tail_moves(snake.body()) {
+            board.unblock(snake.get_tail_position());
+        }
+
+        board.neighbours(head)
+            .map(|next| {
+                let body = simulate(snake.body(), &[next], false);
+                let tail = body[body.len() - 1];
+                let mut after = Board::with_body(&body, obstacles, grid, boundary_mode);
+                after.unblock(tail);
+
+                let tail_distance = after.shortest_path(next, |position| position == tail).map(|path| path.len());
+                let area = after.reachable_area(next);
+
+                ((tail_distance.is_some(), tail_distance.unwrap_or_default(), area), next)
+            })
+            .max_by_key(|&(score, _)| score)
+            .map(|(_, next)| direction_between(head, next))
+    }
+}
+
+// A freshly grown tail sits on the segment before it and stays put for one move.
+fn tail_moves(body: &[(i32, i32)]) -> bool {
+    body.len() < 2 || body[body.len() - 1] != body[body.len() - 2]
+}
+
+fn is_tail_reachable(body: &[(i32, i32)], obstacles: &[Obstacle], grid: GridSize, boundary_mode: BoundaryMode) -> bool {
+    let head = body[0];
+    let tail = body[body.len() - 1];
+    let mut board = Board::with_body(body, obstacles, grid, boundary_mode);
+    board.unblock(tail);
+
+    board.shortest_path(head, |position| position == tail).is_some()
+}
+
+// The body after walking `path`. When `eats` is set the last step ends on food,
+// so the tail stays where it was, as it does after `Snake::grow`.
+fn simulate(body: &[(i32, i32)], path: &[(i32, i32)], eats: bool) -> Vec<(i32, i32)> {
+    let mut body: VecDeque<_> = body.iter().copied().collect();
+
+    for (index, &step) in path.iter().enumerate() {
+        body.push_front(step);
+
+        if !(eats && index == path.len() - 1) {
+            body.pop_back();
+        }
+    }
+
+    body.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::snake_at;
+    use crate::ai::GreedyBfsAi;
+
+    #[test]
+    fn refuses_food_that_traps_the_snake() {
+        let snake = snake_at((3, 2), 3);
+        // A one cell pocket at (3, 3) whose only exit is the cell the head is on.
+        let walls = Obstacle::from_cells([0.5; 4], [(2, 3), (4, 3), (3, 4)]);
+        let food = [Food::new([1.0; 4], (3, 3))];
+        let obstacles = [walls];
+
+        let greedy = GreedyBfsAi::new().get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Solid);
+        let survival = SurvivalAi::new().get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Solid);
+
+        assert_eq!(greedy, Some(Direction::Up), "Greedy walks straight into the pocket");
+        assert!(survival.is_some());
+        assert_ne!(survival, Some(Direction::Up), "Survival should stay out of the pocket");
+    }
+
+    #[test]
+    fn keeps_moving_without_reachable_food() {
+        let snake = snake_at((5, 5), 4);
+        let walls = Obstacle::from_cells([0.5; 4], [(8, 7), (7, 8), (9, 8), (8, 9)]);
+        let food = [Food::new([1.0; 4], (8, 8))];
+        let obstacles = [walls];
+
+        assert_eq!(GreedyBfsAi::new().get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Solid), None);
+        assert!(SurvivalAi::new().get_direction(&snake, &food, &obstacles, GridSize::square(10), BoundaryMode::Solid).is_some());
+    }
+
+    #[test]
+    fn simulated_body_grows_on_the_last_step() {
+        let body = [(2, 0), (1, 0), (0, 0)];
+
+        assert_eq!(simulate(&body, &[(3, 0), (4, 0)], true), vec![(4, 0), (3, 0), (2, 0), (1, 0)]);
+        assert_eq!(simulate(&body, &[(3, 0)], false), vec![(3, 0), (2, 0), (1, 0)]);
+    }
+}
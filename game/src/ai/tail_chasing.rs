@@ -0,0 +1,88 @@
+use crate::{abstractions::AiController, models::{BoundaryMode, Direction, GridSize}, objects::{Food, Obstacle, Snake}};
+
+use super::{direction_between, Board};
+
+// Eats only when the tail is still reachable afterwards and otherwise follows its own tail,
+// which keeps an escape route open for as long as possible.
+#[derive(Default)]
+pub struct TailChasingAi;
+
+impl AiController for TailChasingAi {
+    fn get_direction(
+        &self,
+        snake: &Snake,
+        foods: &[Food],
+        obstacles: &[Obstacle],
+        grid: GridSize,
+        boundary_mode: BoundaryMode,
+    ) -> Option<Direction> {
+        let board = Board::new(snake, obstacles, grid, boundary_mode);
+        let head = snake.get_head_position();
+        let tail = snake.get_tail_position();
+
+        let food_path = board.shortest_path(head, |position| foods.iter().any(|food| food.position == position));
+
+        if let Some(next) = food_path.and_then(|path| path.first().copied()) {
+            if self.can_reach_tail(&board, snake, next) {
+                return Some(direction_between(head, next));
+            }
+        }
+
+        // The tail moves out of the way, so its cell is a safe target.
+        let mut chase_board = board.clone();
+        chase_board.unblock(tail);
+
+        if let Some(next) = chase_board.shortest_path(head, |position| position == tail).and_then(|path| path.first().copied()) {
+            return Some(direction_between(head, next));
+        }
+
+        board.neighbours(head)
+            .max_by_key(|&neighbour| board.reachable_area(neighbour))
+            .map(|next| direction_between(head, next))
+    }
+}
+
+impl TailChasingAi {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn can_reach_tail(&self, board: &Board, snake: &Snake, next: (i32, i32)) -> bool {
+        let tail = snake.get_tail_position();
+        let mut board = board.clone();
+        board.block(next);
+        board.unblock(tail);
+
+        board.shortest_path(next, |position| position == tail).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::snake_at;
+
+    #[test]
+    fn skips_food_in_dead_end() {
+        let ai = TailChasingAi::new();
+        let snake = snake_at((3, 2), 3);
+        // A one cell pocket at (3, 3): entering it leaves no way back out.
+        let walls = Obstacle::from_cells([0.5; 4], [(2, 3), (4, 3), (3, 4)]);
+        let food = Food::new([1.0; 4], (3, 3));
+
+        let direction = ai.get_direction(&snake, &[food], &[walls], GridSize::square(10), BoundaryMode::Solid);
+
+        assert!(direction.is_some());
+        assert_ne!(direction, Some(Direction::Up), "Snake should not enter the pocket");
+    }
+
+    #[test]
+    fn follows_tail_without_food() {
+        let ai = TailChasingAi::new();
+        let snake = snake_at((5, 5), 4);
+
+        let direction = ai.get_direction(&snake, &[], &[], GridSize::square(10), BoundaryMode::Solid);
+
+        assert!(matches!(direction, Some(Direction::Up) | Some(Direction::Down)), "Tail is behind, so the snake has to turn");
+    }
+}
@@ -0,0 +1,179 @@
+use std::{env, process, time::{SystemTime, UNIX_EPOCH}};
+
+use snake_game::{
+    abstractions::AiController,
+    ai::create_ai_controller,
+    game::Game,
+    models::{AiStrategy, BoundaryMode, Difficulty, GameOptions, GameResult, GameStats, DEFAULT_INPUT_BUFFER},
+    randomizer::SeededRandomizer,
+};
+
+struct Arguments {
+    games: u32,
+    max_ticks: u32,
+    seed: u64,
+    ai: String,
+    options: GameOptions,
+}
+
+struct RunResult {
+    stats: GameStats,
+    died: bool,
+}
+
+fn print_usage() {
+    println!("Usage: headless [--games N] [--ai greedy|astar|hamiltonian|tail|survival] [--seed S] [--grid-size N] [--grid-width N] [--grid-height N] [--food-count N] [--difficulty easy|hard] [--boundary wrap|solid|wrap-horizontal|wrap-vertical] [--max-ticks N]");
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", name))?;
+    value.parse().map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
+
+fn parse_arguments() -> Result<Arguments, String> {
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_nanos() as u64).unwrap_or_default();
+
+    let mut arguments = Arguments {
+        games: 100,
+        max_ticks: 10_000,
+        seed,
+        ai: "greedy".into(),
+        options: GameOptions {
+            snake_color: "#FFFFFF".into(),
+            grid_width: 30,
+            grid_height: 30,
+            food_count: 5,
+            fps: 10,
+            input_buffer: DEFAULT_INPUT_BUFFER,
+            ..GameOptions::default()
+        },
+    };
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => arguments.games = parse_value(&arg, args.next())?,
+            "--max-ticks" => arguments.max_ticks = parse_value(&arg, args.next())?,
+            "--seed" => arguments.seed = parse_value(&arg, args.next())?,
+            "--ai" => arguments.ai = parse_value(&arg, args.next())?,
+            "--grid-size" => {
+                let size = parse_value(&arg, args.next())?;
+                arguments.options.grid_width = size;
+                arguments.options.grid_height = size;
+            },
+            "--grid-width" => arguments.options.grid_width = parse_value(&arg, args.next())?,
+            "--grid-height" => arguments.options.grid_height = parse_value(&arg, args.next())?,
+            "--food-count" => arguments.options.food_count = parse_value(&arg, args.next())?,
+            "--difficulty" => {
+                arguments.options.difficulty = match parse_value::<String>(&arg, args.next())?.as_str() {
+                    "easy" => Difficulty::Easy,
+                    "hard" => Difficulty::Hard,
+                    other => return Err(format!("Unknown difficulty: {}", other)),
+                }
+            },
+            "--boundary" => {
+                arguments.options.boundary_mode = match parse_value::<String>(&arg, args.next())?.as_str() {
+                    "wrap" => BoundaryMode::Wrap,
+                    "solid" => BoundaryMode::Solid,
+                    "wrap-horizontal" => BoundaryMode::WrapHorizontal,
+                    "wrap-vertical" => BoundaryMode::WrapVertical,
+                    other => return Err(format!("Unknown boundary mode: {}", other)),
+                }
+            },
+            "--help" | "-h" => {
+                print_usage();
+                process::exit(0);
+            },
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    if let Err(errors) = arguments.options.validate() {
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        return Err(messages.join("\n"));
+    }
+
+    Ok(arguments)
+}
+
+fn create_ai(name: &str) -> Result<Box<dyn AiController>, String> {
+    match name {
+        "greedy" => Ok(create_ai_controller(AiStrategy::Greedy)),
+        "astar" => Ok(create_ai_controller(AiStrategy::AStar)),
+        "hamiltonian" => Ok(create_ai_controller(AiStrategy::Hamiltonian)),
+        "tail" => Ok(create_ai_controller(AiStrategy::TailChasing)),
+        "survival" => Ok(create_ai_controller(AiStrategy::Survival)),
+        other => Err(format!("Unknown ai: {}", other)),
+    }
+}
+
+fn run_game(game: &mut Game<SeededRandomizer>, ai: &dyn AiController, options: &GameOptions, max_ticks: u32) -> RunResult {
+    game.reset();
+
+    while game.tick() < max_ticks {
+        let direction = ai.get_direction(&game.players[0].snake, &game.foods, &game.obstacles, options.grid(), options.boundary_mode);
+
+        if let Some(direction) = direction {
+            game.change_direction(direction);
+        }
+
+        if game.update() == GameResult::Over {
+            return RunResult { stats: game.stats().clone(), died: true };
+        }
+    }
+
+    RunResult { stats: game.stats().clone(), died: false }
+}
+
+fn main() {
+    let arguments = match parse_arguments() {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            eprintln!("{}", error);
+            print_usage();
+            process::exit(1);
+        }
+    };
+
+    let ai = match create_ai(&arguments.ai) {
+        Ok(ai) => ai,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut game = Game::new(arguments.options.clone(), SeededRandomizer::new(arguments.seed));
+
+    if let Err(error) = game.initialize() {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+
+    let results: Vec<RunResult> = (0..arguments.games)
+        .map(|_| run_game(&mut game, ai.as_ref(), &arguments.options, arguments.max_ticks))
+        .collect();
+
+    if results.is_empty() {
+        return;
+    }
+
+    let count = results.len() as f64;
+    let scores: Vec<u32> = results.iter().map(|result| result.stats.score).collect();
+    let mean_score = scores.iter().sum::<u32>() as f64 / count;
+    let mean_ticks = results.iter().map(|result| result.stats.ticks as f64).sum::<f64>() / count;
+    let mean_max_length = results.iter().map(|result| result.stats.max_length as f64).sum::<f64>() / count;
+    let deaths = results.iter().filter(|result| result.died).count();
+
+    println!("ai:          {}", arguments.ai);
+    println!("seed:        {}", arguments.seed);
+    println!("games:       {}", results.len());
+    println!("score mean:  {:.2}", mean_score);
+    println!("score min:   {}", scores.iter().min().unwrap());
+    println!("score max:   {}", scores.iter().max().unwrap());
+    println!("ticks mean:  {:.2}", mean_ticks);
+    println!("length mean: {:.2}", mean_max_length);
+    println!("deaths:      {}", deaths);
+    println!("timeouts:    {}", results.len() - deaths);
+}
@@ -0,0 +1,295 @@
+// Every export here reaches into the `static mut` state below. JS calls them from its
+// single thread, one at a time, which is all the safety they rely on.
+#![allow(unsafe_op_in_unsafe_fn, clippy::missing_safety_doc)]
+
+use std::rc::Rc;
+use std::{cell::RefCell, panic};
+use cfg_if::cfg_if;
+use js_sys::Function;
+use log::debug;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::GameError;
+use crate::game_orchestrator::{GameOrchestrator, WasmGameOrchestrator};
+use crate::map::GameMap;
+use crate::game_orchestrator_factory::{GameOrchestratorFactory, WasmGameOrchestratorFactory};
+use crate::js_transport::{JsTransport, ServerInbox};
+use crate::models::{GameOptions, GameState};
+use crate::net::ServerMessage;
+use crate::replay::Replay;
+use crate::save::SavedGame;
+use crate::utils::to_js_value;
+
+static mut GAME_ORCHESTRATOR: Option<Rc<RefCell<WasmGameOrchestrator<Function>>>> = None;
+static mut SERVER_INBOX: Option<ServerInbox> = None;
+
+#[derive(Serialize)]
+struct FieldError {
+    field: String,
+    message: String,
+}
+
+unsafe fn game_orchestrator() -> Result<Rc<RefCell<WasmGameOrchestrator<Function>>>, GameError> {
+    GAME_ORCHESTRATOR.clone().ok_or(GameError::NotInitialized)
+}
+
+// Runs `action` on the orchestrator and sends the events it caused once the borrow is released,
+// so subscribers are free to call back into any export.
+unsafe fn update_orchestrator<U>(action: impl FnOnce(&mut WasmGameOrchestrator<Function>) -> Result<U, GameError>) -> Result<U, GameError> {
+    let orchestrator = game_orchestrator()?;
+    let result = action(&mut orchestrator.borrow_mut());
+    let sent = WasmGameOrchestrator::send_events(&orchestrator);
+
+    // What went wrong with the action itself matters more than a subscriber failing on its events.
+    let value = result?;
+    sent?;
+
+    Ok(value)
+}
+
+cfg_if! {
+    if #[cfg(feature = "console_log")] {
+        fn init_log() {
+            use log::Level;
+            #[cfg(debug_assertions)]
+            console_log::init_with_level(Level::Debug).ok();
+            
+            #[cfg(not(debug_assertions))]
+            console_log::init_with_level(Level::Warn).ok();
+        }
+    } else {
+        fn init_log() {}
+    }
+}
+
+#[wasm_bindgen]
+pub unsafe fn setup(
+    options: JsValue,
+    on_score: Function,
+    on_game_over: Function) -> Result<(), JsValue> {
+    panic::set_hook(Box::new(console_error_panic_hook::hook));
+    init_log();
+
+    let options: GameOptions = serde_wasm_bindgen::from_value(options)
+        .map_err(|error| GameError::InvalidOptions(error.to_string()))?;
+
+    let game_orchestrator = WasmGameOrchestratorFactory::create(options, on_score, on_game_over)?;
+
+    {
+        let mut orchestrator = game_orchestrator.borrow_mut();
+        orchestrator.initialize()?;
+        orchestrator.resize();
+    }
+    
+
+    GAME_ORCHESTRATOR = Some(game_orchestrator.clone());
+
+    WasmGameOrchestrator::setup_on_resize(game_orchestrator.clone());
+    WasmGameOrchestrator::setup_key_bindings(game_orchestrator);
+
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "applyOptions")]
+pub unsafe fn apply_options(options: JsValue) -> Result<(), JsValue> {
+    let options: GameOptions = serde_wasm_bindgen::from_value(options)
+        .map_err(|error| GameError::InvalidOptions(error.to_string()))?;
+
+    update_orchestrator(|orchestrator| orchestrator.apply_options_and_reset(options))?;
+
+    Ok(())
+}
+
+// Lists what is wrong with the options, one `{ field, message }` per problem, without applying them.
+// `applyOptions` pulls numbers back into range on its own, this is for showing the user why.
+#[wasm_bindgen(js_name = "validateOptions")]
+pub fn validate_options(options: JsValue) -> Result<JsValue, JsValue> {
+    let options: GameOptions = serde_wasm_bindgen::from_value(options)
+        .map_err(|error| GameError::InvalidOptions(error.to_string()))?;
+
+    let errors: Vec<FieldError> = options.validate().err().unwrap_or_default().iter()
+        .map(|error| FieldError { field: error.field(), message: error.to_string() })
+        .collect();
+
+    Ok(to_js_value(&errors)?)
+}
+
+#[wasm_bindgen]
+pub unsafe fn play(#[wasm_bindgen(js_name = "isAiPlaying")]is_ai_playing: bool) -> Result<(), JsValue> {
+    debug!("play");
+    restart(is_ai_playing)
+}
+
+#[wasm_bindgen]
+pub unsafe fn restart(#[wasm_bindgen(js_name = "isAiPlaying")]is_ai_playing: bool) -> Result<(), JsValue> {
+    let state = if is_ai_playing { GameState::AiPlaying } else { GameState::UserPlaying };
+    GameOrchestrator::restart(game_orchestrator()?, state)?;
+
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub unsafe fn pause() -> Result<(), JsValue> {
+    update_orchestrator(|orchestrator| orchestrator.pause())?;
+
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub unsafe fn resume() -> Result<(), JsValue> {
+    update_orchestrator(|orchestrator| orchestrator.resume())?;
+
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "loadMap")]
+pub unsafe fn load_map(map: JsValue) -> Result<(), JsValue> {
+    let map: GameMap = serde_wasm_bindgen::from_value(map)
+        .map_err(|error| GameError::UnreadableMap(error.to_string()))?;
+    map.validate().map_err(GameError::from)?;
+
+    update_orchestrator(|orchestrator| orchestrator.load_map(map))?;
+
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "unloadMap")]
+pub unsafe fn unload_map() -> Result<(), JsValue> {
+    update_orchestrator(|orchestrator| orchestrator.unload_map())?;
+
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub unsafe fn subscribe(
+    #[wasm_bindgen(js_name = "eventName")] event_name: String,
+    callback: Function) -> Result<u32, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let mut orchestrator = orchestrator.borrow_mut();
+    let id = orchestrator.subscribe(&event_name, callback);
+
+    Ok(id)
+}
+
+#[wasm_bindgen]
+pub unsafe fn unsubscribe(id: u32) -> Result<bool, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let mut orchestrator = orchestrator.borrow_mut();
+
+    Ok(orchestrator.unsubscribe(id))
+}
+
+#[wasm_bindgen(js_name = "getStats")]
+pub unsafe fn get_stats() -> Result<JsValue, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let orchestrator = orchestrator.borrow();
+    let stats = to_js_value(orchestrator.get_stats())?;
+
+    Ok(stats)
+}
+
+// Where the grid is drawn, in CSS pixels, so UI can be lined up with the cells.
+#[wasm_bindgen(js_name = "getViewport")]
+pub unsafe fn get_viewport() -> Result<JsValue, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let orchestrator = orchestrator.borrow();
+    let viewport = to_js_value(orchestrator.viewport())?;
+
+    Ok(viewport)
+}
+
+#[wasm_bindgen(js_name = "getReplay")]
+pub unsafe fn get_replay() -> Result<JsValue, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let orchestrator = orchestrator.borrow();
+    let replay = to_js_value(orchestrator.get_replay()?)?;
+
+    Ok(replay)
+}
+
+#[wasm_bindgen(js_name = "playReplay")]
+pub unsafe fn play_replay(replay: JsValue) -> Result<(), JsValue> {
+    let replay: Replay = serde_wasm_bindgen::from_value(replay)
+        .map_err(|error| GameError::InvalidReplay(error.to_string()))?;
+
+    update_orchestrator(|orchestrator| orchestrator.play_replay(replay))?;
+
+    GameOrchestrator::restart(game_orchestrator()?, GameState::Replaying)?;
+
+    Ok(())
+}
+
+// Everything needed to carry on with the current game later, as a plain object that survives JSON.stringify.
+#[wasm_bindgen(js_name = "saveGame")]
+pub unsafe fn save_game() -> Result<JsValue, JsValue> {
+    let orchestrator = game_orchestrator()?;
+    let orchestrator = orchestrator.borrow();
+    let saved = to_js_value(&orchestrator.save_game())?;
+
+    Ok(saved)
+}
+
+// Continues a game from `saveGame` with whoever was playing it.
+#[wasm_bindgen(js_name = "loadGame")]
+pub unsafe fn load_game(saved: JsValue) -> Result<(), JsValue> {
+    let saved: SavedGame = serde_wasm_bindgen::from_value(saved)
+        .map_err(|error| GameError::InvalidSave(error.to_string()))?;
+
+    update_orchestrator(|orchestrator| orchestrator.load_game(&saved))?;
+
+    GameOrchestrator::start_game_loop(game_orchestrator()?, saved.state)?;
+
+    Ok(())
+}
+
+// Turns the game into a client of a remote server. `send` receives every message for the server,
+// and whatever the server answers has to be passed to `receiveServerMessage`.
+#[wasm_bindgen]
+pub unsafe fn connect(send: Function) -> Result<(), JsValue> {
+    let (transport, inbox) = JsTransport::new(send);
+    SERVER_INBOX = Some(inbox);
+
+    let orchestrator = game_orchestrator()?;
+    let mut orchestrator = orchestrator.borrow_mut();
+    orchestrator.connect(Box::new(transport));
+
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "receiveServerMessage")]
+pub unsafe fn receive_server_message(message: JsValue) -> Result<(), JsValue> {
+    let message: ServerMessage = serde_wasm_bindgen::from_value(message)
+        .map_err(|error| GameError::InvalidMessage(error.to_string()))?;
+
+    match SERVER_INBOX.as_ref() {
+        Some(inbox) => inbox.borrow_mut().push_back(message),
+        None => return Err(GameError::NotConnected.into()),
+    }
+
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub unsafe fn disconnect() -> Result<(), JsValue> {
+    SERVER_INBOX = None;
+
+    update_orchestrator(|orchestrator| orchestrator.disconnect())?;
+
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub unsafe fn stop() -> Result<(), JsValue> {
+    // Unlike `pause`, stopping something that is not running is fine.
+    update_orchestrator(|orchestrator| {
+        if orchestrator.state().is_running() {
+            orchestrator.pause()
+        }
+        else {
+            Ok(())
+        }
+    })?;
+
+    Ok(())
+}
\ No newline at end of file
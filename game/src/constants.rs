@@ -1,13 +1,25 @@
-// It takes a 2D position (`a_position`) as input and passes it 
-// through directly to the pipeline, with the z-coordinate set to 0 and w-coordinate set to 1.
+// Every cell is drawn as an instance of one unit square. `a_corner` is the square's corner,
+// the other attributes change per instance: the cell on the grid, its color and what it is.
+// The square is placed in clip space from `u_grid`, the grid's size in cells, and shrunk by
+// `u_spacing` to leave a gap between cells. Snakes (kind 0) keep a wider gap above each segment.
 pub const VS_SOURCE: &str = r#"
-    attribute vec2 a_position;
+    attribute vec2 a_corner;
+    attribute vec2 a_cell;
     attribute vec4 a_color;
+    attribute float a_kind;
+
+    uniform vec2 u_grid;
+    uniform float u_spacing;
 
     varying vec4 v_color;
 
     void main() {
-        gl_Position = vec4(a_position, 0.0, 1.0);
+        vec2 cell_size = 2.0 / u_grid;
+        vec2 origin = a_cell * cell_size - 1.0 + u_spacing;
+        float top_spacing = a_kind < 0.5 ? u_spacing * 1.4 : u_spacing;
+        vec2 size = cell_size - vec2(u_spacing, top_spacing);
+
+        gl_Position = vec4(origin + a_corner * size, 0.0, 1.0);
         v_color = a_color;
     }
 "#;
@@ -22,6 +34,9 @@ pub const FS_SOURCE: &str = r#"
     }
 "#;
 
+// The gap around each cell, in clip space.
+pub const CELL_SPACING: f32 = 0.01;
+
 pub const COLORS: &[[f32; 4]] = &[
     [1.0, 0.0, 0.0, 1.0],
     [0.0, 1.0, 0.0, 1.0],
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use crate::{map::MapError, models::InvalidTransition};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GameError {
+    NotInitialized,
+    InvalidOptions(String),
+    InvalidColor(String),
+    InvalidMap(Vec<MapError>),
+    UnreadableMap(String),
+    InvalidReplay(String),
+    UnsupportedReplay(u32),
+    NoReplay,
+    InvalidSave(String),
+    UnsupportedSave(u32),
+    InvalidMessage(String),
+    NoWindow,
+    CanvasNotFound(String),
+    WebGl2Unavailable,
+    ShaderCompile(String),
+    ProgramLink(String),
+    InvalidTransition(InvalidTransition),
+    NotConnected,
+    CallbackFailed(String),
+}
+
+impl GameError {
+    // A stable name for the error, so callers can tell errors apart without parsing the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::NotInitialized => "notInitialized",
+            GameError::InvalidOptions(_) => "invalidOptions",
+            GameError::InvalidColor(_) => "invalidColor",
+            GameError::InvalidMap(_) => "invalidMap",
+            GameError::UnreadableMap(_) => "unreadableMap",
+            GameError::InvalidReplay(_) => "invalidReplay",
+            GameError::UnsupportedReplay(_) => "unsupportedReplay",
+            GameError::NoReplay => "noReplay",
+            GameError::InvalidSave(_) => "invalidSave",
+            GameError::UnsupportedSave(_) => "unsupportedSave",
+            GameError::InvalidMessage(_) => "invalidMessage",
+            GameError::NoWindow => "noWindow",
+            GameError::CanvasNotFound(_) => "canvasNotFound",
+            GameError::WebGl2Unavailable => "webGl2Unavailable",
+            GameError::ShaderCompile(_) => "shaderCompile",
+            GameError::ProgramLink(_) => "programLink",
+            GameError::InvalidTransition(_) => "invalidTransition",
+            GameError::NotConnected => "notConnected",
+            GameError::CallbackFailed(_) => "callbackFailed",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotInitialized => write!(f, "The game has not been set up, call setup first"),
+            GameError::InvalidOptions(message) => write!(f, "Invalid options: {}", message),
+            GameError::InvalidColor(color) => write!(f, "{:?} is not a valid color", color),
+            GameError::InvalidMap(errors) => {
+                let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "Invalid map:\n{}", messages.join("\n"))
+            }
+            GameError::UnreadableMap(message) => write!(f, "Map could not be read: {}", message),
+            GameError::InvalidReplay(message) => write!(f, "Invalid replay: {}", message),
+            GameError::UnsupportedReplay(version) => write!(f, "Unsupported replay version {}", version),
+            GameError::NoReplay => write!(f, "A loaded game has no replay, it did not start from a fresh board"),
+            GameError::InvalidSave(message) => write!(f, "Invalid saved game: {}", message),
+            GameError::UnsupportedSave(version) => write!(f, "Unsupported saved game version {}", version),
+            GameError::InvalidMessage(message) => write!(f, "Invalid server message: {}", message),
+            GameError::NoWindow => write!(f, "No window or document to run in"),
+            GameError::CanvasNotFound(id) => write!(f, "No canvas with id {:?}", id),
+            GameError::WebGl2Unavailable => write!(f, "WebGL2 is not available"),
+            GameError::ShaderCompile(log) => write!(f, "Shader failed to compile: {}", log),
+            GameError::ProgramLink(log) => write!(f, "Shader program failed to link: {}", log),
+            GameError::InvalidTransition(transition) => write!(f, "{}", transition),
+            GameError::NotConnected => write!(f, "Not connected to a server"),
+            GameError::CallbackFailed(message) => write!(f, "A JS callback failed: {}", message),
+        }
+    }
+}
+
+impl From<InvalidTransition> for GameError {
+    fn from(transition: InvalidTransition) -> Self {
+        GameError::InvalidTransition(transition)
+    }
+}
+
+impl From<Vec<MapError>> for GameError {
+    fn from(errors: Vec<MapError>) -> Self {
+        GameError::InvalidMap(errors)
+    }
+}
+
+// JS gets a regular Error carrying the code, so `catch` blocks can check `error.code`.
+#[cfg(feature = "wasm")]
+impl From<GameError> for wasm_bindgen::JsValue {
+    fn from(error: GameError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("GameError");
+        js_sys::Reflect::set(&js_error, &"code".into(), &error.code().into()).ok();
+        js_error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GameState;
+
+    #[test]
+    fn converts_transition_and_map_errors() {
+        let transition = InvalidTransition { from: GameState::Idle, to: GameState::Paused };
+        let error: GameError = transition.into();
+        assert_eq!(error.code(), "invalidTransition");
+        assert_eq!(error.to_string(), "Cannot go from Idle to Paused");
+
+        let error: GameError = vec![MapError::SpawnTooShort, MapError::NoFreeFoodCell].into();
+        assert_eq!(error.code(), "invalidMap");
+        assert_eq!(error.to_string().lines().count(), 3, "Each map error should get its own line");
+    }
+}
@@ -0,0 +1,149 @@
+use std::rc::Rc;
+
+use crate::{abstractions::InvokeJs, error::GameError, events::GameEvent};
+
+pub type SubscriptionId = u32;
+
+struct Subscription<T: InvokeJs> {
+    id: SubscriptionId,
+    event_name: String,
+    callback: Rc<T>,
+}
+
+// Emitted events wait in a queue until they are taken out with their subscribers, so nothing
+// is called while whoever emitted them is still in the middle of something.
+pub struct EventBus<T: InvokeJs> {
+    next_id: SubscriptionId,
+    subscriptions: Vec<Subscription<T>>,
+    queued: Vec<GameEvent>,
+}
+
+// Events paired with the callbacks they go to, ready to be sent once it is safe to call out.
+pub struct Deliveries<T: InvokeJs>(Vec<(Rc<T>, GameEvent)>);
+
+impl<T: InvokeJs> Deliveries<T> {
+    // A callback that fails does not keep the event from the others, the first failure is returned once all were called.
+    pub fn send(self) -> Result<(), GameError> {
+        let mut result = Ok(());
+
+        for (callback, event) in self.0 {
+            let sent = callback.invoke(&event);
+            result = result.and(sent);
+        }
+
+        result
+    }
+}
+
+impl<T: InvokeJs> Default for EventBus<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: InvokeJs> EventBus<T> {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            subscriptions: vec![],
+            queued: vec![],
+        }
+    }
+
+    pub fn subscribe(&mut self, event_name: &str, callback: T) -> SubscriptionId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.subscriptions.push(Subscription {
+            id,
+            event_name: event_name.to_string(),
+            callback: Rc::new(callback),
+        });
+
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let count = self.subscriptions.len();
+        self.subscriptions.retain(|subscription| subscription.id != id);
+        self.subscriptions.len() != count
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queued.push(event);
+    }
+
+    // Everything emitted so far, in order, with whoever is subscribed to it right now.
+    pub fn take_deliveries(&mut self) -> Deliveries<T> {
+        let deliveries = self.queued.drain(..)
+            .flat_map(|event| {
+                let event_name = event.name();
+
+                self.subscriptions.iter()
+                    .filter(move |subscription| subscription.event_name == event_name)
+                    .map(move |subscription| (subscription.callback.clone(), event.clone()))
+            })
+            .collect();
+
+        Deliveries(deliveries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::invoke_js::MockInvokeJsStub;
+
+    fn callback(times: usize) -> MockInvokeJsStub {
+        let mut callback = MockInvokeJsStub::new();
+        callback.expect_invoke().times(times).return_const(Ok(()));
+        callback
+    }
+
+    #[test]
+    fn emits_only_to_matching_subscribers() {
+        let mut event_bus = EventBus::new();
+        event_bus.subscribe("paused", callback(1));
+        event_bus.subscribe("resumed", callback(0));
+
+        event_bus.emit(GameEvent::Paused);
+        event_bus.take_deliveries().send().unwrap();
+    }
+
+    #[test]
+    fn holds_events_until_they_are_taken() {
+        let mut event_bus = EventBus::new();
+        event_bus.subscribe("paused", callback(1));
+
+        event_bus.emit(GameEvent::Paused);
+        let deliveries = event_bus.take_deliveries();
+
+        assert!(event_bus.take_deliveries().0.is_empty(), "Taken events should not be sent twice");
+        deliveries.send().unwrap();
+    }
+
+    #[test]
+    fn unsubscribed_callback_is_not_invoked() {
+        let mut event_bus = EventBus::new();
+        let id = event_bus.subscribe("paused", callback(0));
+
+        assert!(event_bus.unsubscribe(id));
+        assert!(!event_bus.unsubscribe(id), "Unsubscribing twice should report nothing removed");
+
+        event_bus.emit(GameEvent::Paused);
+        event_bus.take_deliveries().send().unwrap();
+    }
+
+    #[test]
+    fn keeps_sending_after_a_callback_fails() {
+        let mut event_bus = EventBus::new();
+        let mut failing = MockInvokeJsStub::new();
+        failing.expect_invoke().times(1).return_const(Err(GameError::CallbackFailed("boom".into())));
+        event_bus.subscribe("paused", failing);
+        event_bus.subscribe("paused", callback(1));
+
+        event_bus.emit(GameEvent::Paused);
+
+        assert_eq!(event_bus.take_deliveries().send(), Err(GameError::CallbackFailed("boom".into())));
+    }
+}
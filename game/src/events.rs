@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Direction, FoodKind, GameStats};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum GameOverReason {
+    SelfCollision,
+    Wall,
+    Obstacle,
+    HeadToHead,
+    HeadToBody,
+    Poisoned
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum GameEvent {
+    FoodEaten {
+        player: usize,
+        kind: FoodKind,
+        position: (i32, i32),
+        stats: GameStats
+    },
+    DirectionChanged {
+        player: usize,
+        direction: Direction
+    },
+    Paused,
+    Resumed,
+    LevelUp {
+        level: u32
+    },
+    FoodExpired {
+        kind: FoodKind,
+        position: (i32, i32)
+    },
+    ObstacleSpawned {
+        position: (i32, i32),
+        cells: Vec<(i32, i32)>
+    },
+    GameOver {
+        reason: GameOverReason,
+        stats: GameStats
+    },
+    PlayerDied {
+        player: usize,
+        reason: GameOverReason,
+        stats: GameStats
+    },
+    MatchOver {
+        winner: Option<usize>,
+        players: Vec<GameStats>
+    }
+}
+
+impl GameEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::FoodEaten { .. } => "foodEaten",
+            GameEvent::DirectionChanged { .. } => "directionChanged",
+            GameEvent::Paused => "paused",
+            GameEvent::Resumed => "resumed",
+            GameEvent::LevelUp { .. } => "levelUp",
+            GameEvent::FoodExpired { .. } => "foodExpired",
+            GameEvent::ObstacleSpawned { .. } => "obstacleSpawned",
+            GameEvent::GameOver { .. } => "gameOver",
+            GameEvent::PlayerDied { .. } => "playerDied",
+            GameEvent::MatchOver { .. } => "matchOver",
+        }
+    }
+}
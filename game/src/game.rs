@@ -1,15 +1,32 @@
 use log::debug;
 
 use csscolorparser::Color;
-use crate::{models::{Difficulty, Direction, GameOptions, GameResult}, objects::{Food, Obstacle, Snake}, randomizer::Randomizer};
+use crate::{error::GameError, events::{GameEvent, GameOverReason}, map::GameMap, models::{CellInstance, CellKind, Difficulty, Direction, FoodKind, GameOptions, GameResult, GameState, GameStats, GridSize, Level, OptionsError, MAX_GRID_SIZE, MIN_GRID_SIZE}, net::{FoodSnapshot, GameSnapshot, SpeedEffect}, objects::{Food, Obstacle, Snake}, player::Player, randomizer::Randomizer, save::{SavedGame, SAVE_VERSION}};
+
+const OBSTACLE_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+const DEFAULT_SPAWN_POSITION: (i32, i32) = (10, 10);
+const MAX_RANDOM_PLACEMENT_ATTEMPTS: u32 = 100;
+const DEFAULT_BODY_LENGTH: usize = 5;
+const SPEED_EFFECT_TICKS: u32 = 50;
+const SHRINK_MIN_LENGTH: usize = 2;
+// Obstacles added on a level up keep at least this many cells away from every head.
+const LEVEL_OBSTACLE_CLEARANCE: i32 = 2;
 
 pub struct Game<R: Randomizer> {
     options: GameOptions,
-    pub direction: Direction,
-    pub snake: Snake,
+    base_options: GameOptions,
+    pub players: Vec<Player>,
     pub foods: Vec<Food>,
     pub obstacles: Vec<Obstacle>,
-    cell_size: f32,
+    ticks: u32,
+    ai_playing: bool,
+    events: Vec<GameEvent>,
+    map: Option<GameMap>,
+    seed: u64,
+    // Speed food changes how fast the whole game ticks, whoever ate it. Every snake moves
+    // one cell per tick, so in a match the other snakes speed up or slow down too.
+    speed_effect: Option<SpeedEffect>,
+    level: u32,
     randomizer: R
 }
 
@@ -17,182 +34,774 @@ impl<R: Randomizer> Game< R> {
     pub fn new(
         options: GameOptions,
         randomizer: R) -> Self {
-        let cell_size = 2.0 / options.grid_size as f32;
-        let snake = Snake::new();
+        let options = options.normalized();
 
-        Game {
+        let mut game = Game {
+            base_options: options.clone(),
             options,
-            direction: Direction::Right,
-            snake,
+            players: vec![],
             foods: vec![],
             obstacles: vec![],
-            cell_size,
+            ticks: 0,
+            ai_playing: false,
+            events: vec![],
+            map: None,
+            seed: 0,
+            speed_effect: None,
+            level: 0,
             randomizer
+        };
+
+        game.players = game.create_players();
+        game
+    }
+
+    pub fn initialize(&mut self) -> Result<(), GameError> {
+        let colors = Self::parse_colors(&self.options)?;
+
+        if let Some(seed) = self.options.seed {
+            self.randomizer.reseed(seed);
         }
+
+        self.players = self.create_players();
+        self.set_player_colors(&colors);
+
+        self.spawn_obstacles();
+
+        self.foods = vec![];
+        self.set_food_count(self.options.food_count as usize);
+
+        Ok(())
     }
 
-    pub fn initialize(&mut self) {
-        let body_length = 5;
-        
-        let foods = (0..self.options.food_count).map(|_| Food::new(
-            self.randomizer.get_random_color(),
-            self.randomizer.get_random_position_on_grid(self.options.grid_size), self.cell_size)).collect();
-        self.foods = foods;
-        self.snake.initialize(body_length, self.cell_size);
-        
-        let color = self.options.snake_color.parse::<Color>().unwrap();
-        self.snake.set_color(color.to_array());
+    fn create_players(&self) -> Vec<Player> {
+        let player_options = self.options.player_options();
+        let count = player_options.len();
+        let mut players: Vec<Player> = vec![];
+
+        for (index, options) in player_options.into_iter().enumerate() {
+            let taken: Vec<(i32, i32)> = players.iter().flat_map(|player| player.snake.body().to_vec()).collect();
+            let (position, direction, body_length) = self.spawn_for(index, count, &taken);
+
+            let mut snake = Snake::new();
+            snake.set_spawn(position, direction, body_length);
+            snake.initialize(body_length);
+
+            let mut player = Player::new(snake, options.controller);
+            player.set_ai_playing(self.ai_playing);
+            players.push(player);
+        }
+
+        players
+    }
+
+    fn parse_colors(options: &GameOptions) -> Result<Vec<[f32; 4]>, GameError> {
+        options.player_options().iter()
+            .map(|player| player.color.parse::<Color>()
+                .map(|color| color.to_array())
+                .map_err(|_| GameError::InvalidColor(player.color.clone())))
+            .collect()
+    }
+
+    // Normalizing can't pull apart players that share keys, there are only two key sets to hand out.
+    fn check_key_bindings(options: &GameOptions) -> Result<(), GameError> {
+        let overlap = options.validate().err().into_iter().flatten()
+            .find(|error| matches!(error, OptionsError::KeyBindingsOverlap(..)));
+
+        match overlap {
+            Some(error) => Err(GameError::InvalidOptions(error.to_string())),
+            None => Ok(())
+        }
+    }
+
+    fn set_player_colors(&mut self, colors: &[[f32; 4]]) {
+        for (player, &color) in self.players.iter_mut().zip(colors) {
+            player.snake.set_color(color);
+        }
+    }
+
+    // The first player starts where the map says. Several players line up on evenly
+    // spaced rows so none of them starts in another's path. Grids too small for the usual
+    // spot get a centered, shorter snake. On a map the others move to the closest spot
+    // clear of its walls and of the snakes placed before them.
+    fn spawn_for(&self, index: usize, count: usize, taken: &[(i32, i32)]) -> ((i32, i32), Direction, usize) {
+        if let Some(spawn) = self.map.as_ref().map(|map| &map.spawn).filter(|_| index == 0) {
+            return (spawn.position, spawn.direction, spawn.length);
+        }
+
+        let grid = self.options.grid();
+
+        if count == 1 && grid.contains(DEFAULT_SPAWN_POSITION) {
+            return (DEFAULT_SPAWN_POSITION, Direction::Right, DEFAULT_BODY_LENGTH);
+        }
 
-        if self.options.difficulty == Difficulty::Hard {
+        let body_length = DEFAULT_BODY_LENGTH.min(grid.width as usize / 2);
+        let row = (index as i32 + 1) * grid.height / (count as i32 + 1);
+        let column = grid.width / 2 + body_length as i32 / 2;
+
+        let Some(map) = &self.map else {
+            return ((column, row), Direction::Right, body_length);
+        };
+
+        let solid_cells: Vec<(i32, i32)> = map.solid_shapes().into_iter().flatten().collect();
+        let is_clear = |(x, y): (i32, i32)| (0..body_length as i32)
+            .map(|offset| (x - offset, y))
+            .all(|cell| !solid_cells.contains(&cell) && !taken.contains(&cell));
+
+        let mut rows: Vec<i32> = (0..grid.height).collect();
+        rows.sort_by_key(|&candidate| (candidate - row).abs());
+        let mut columns: Vec<i32> = (body_length as i32 - 1..grid.width).collect();
+        columns.sort_by_key(|&candidate| (candidate - column).abs());
+
+        let position = rows.iter()
+            .flat_map(|&y| columns.iter().map(move |&x| (x, y)))
+            .find(|&position| is_clear(position))
+            .unwrap_or((column, row));
+
+        (position, Direction::Right, body_length)
+    }
+
+    fn spawn_obstacles(&mut self) {
+        if let Some(map) = &self.map {
+            let obstacles = map.solid_shapes().into_iter().map(|cells| Obstacle::from_cells(
+                OBSTACLE_COLOR,
+                cells)).collect();
+            self.set_obstacles(obstacles);
+        }
+        else if self.options.difficulty == Difficulty::Hard {
             self.create_obstacles(2);
         }
+        else {
+            self.obstacles = vec![];
+        }
     }
 
+    // Every cell of a block has to be on the board and clear of the snakes and the other blocks.
     fn create_obstacles(&mut self, count: usize) {
-        let obstacles = (0..count).map(|_| Obstacle::new(
-            [0.7, 0.7, 0.7, 1.0],
-            self.randomizer.get_random_position_on_grid(self.options.grid_size), self.cell_size)).collect();
+        let grid = self.options.grid();
+        let mut obstacles: Vec<Obstacle> = vec![];
+
+        for _ in 0..count {
+            let fits = |position| Obstacle::new(OBSTACLE_COLOR, position).cells()
+                .all(|cell| grid.contains(cell) && Self::is_free_cell(cell, &self.players, &obstacles, &[], None));
+
+            match Self::find_position(&mut self.randomizer, grid, fits) {
+                Some(position) => obstacles.push(Obstacle::new(OBSTACLE_COLOR, position)),
+                None => break,
+            }
+        }
+
+        self.set_obstacles(obstacles);
+    }
+
+    fn set_obstacles(&mut self, obstacles: Vec<Obstacle>) {
+        self.events.extend(obstacles.iter().map(|obstacle| GameEvent::ObstacleSpawned {
+            position: obstacle.position(),
+            cells: obstacle.cells().collect(),
+        }));
         self.obstacles = obstacles;
     }
 
+    // What a controller steering player `index` has to avoid: the obstacles and every other live snake.
+    pub fn obstacles_for(&self, index: usize) -> Vec<Obstacle> {
+        let snakes = self.players.iter().enumerate()
+            .filter(|&(other, player)| other != index && player.is_alive())
+            .map(|(_, player)| Obstacle::from_cells(OBSTACLE_COLOR, player.snake.body().iter().copied()));
+
+        self.obstacles.iter().cloned().chain(snakes).collect()
+    }
+
+    pub fn load_map(&mut self, map: GameMap) -> Result<(), GameError> {
+        self.map = Some(map);
+        self.apply_options_and_reset(self.base_options.clone())
+    }
+
+    pub fn unload_map(&mut self) -> Result<(), GameError> {
+        self.map = None;
+        self.apply_options_and_reset(self.base_options.clone())
+    }
+
+    pub fn options(&self) -> &GameOptions {
+        &self.options
+    }
+
+    // The options as they were given, before a loaded map took over the board layout.
+    pub fn base_options(&self) -> &GameOptions {
+        &self.base_options
+    }
+
+    pub fn map(&self) -> Option<&GameMap> {
+        self.map.as_ref()
+    }
+
     fn get_free_position(
         randomizer: &mut R,
-        snake: &Snake,
+        players: &[Player],
         obstacles: &[Obstacle],
         food_positions: &[(i32, i32)],
-        grid_size: i32) -> (i32, i32) {
-        loop {
-            let position = randomizer.get_random_position_on_grid(grid_size);
-            
-            let is_occupied = snake.occupies(position) 
-                || obstacles.iter().any(|obstacle| obstacle.occupies(position))
-                || food_positions.iter().any(|object_position| *object_position == position);
-            
-            if !is_occupied {
-                return position;
+        map: Option<&GameMap>,
+        grid: GridSize) -> Option<(i32, i32)> {
+        Self::find_position(randomizer, grid, |position| Self::is_free_cell(position, players, obstacles, food_positions, map))
+    }
+
+    fn is_free_cell(
+        position: (i32, i32),
+        players: &[Player],
+        obstacles: &[Obstacle],
+        food_positions: &[(i32, i32)],
+        map: Option<&GameMap>) -> bool {
+        !(players.iter().any(|player| player.is_alive() && player.snake.occupies(position))
+            || obstacles.iter().any(|obstacle| obstacle.occupies(position))
+            || food_positions.contains(&position)
+            || !map.is_none_or(|map| map.is_food_cell(position)))
+    }
+
+    // Random picks are cheap while the board is mostly empty. Once they keep missing,
+    // scan the grid instead so the last free spots are still found, or none when there are none.
+    fn find_position(randomizer: &mut R, grid: GridSize, is_free: impl Fn((i32, i32)) -> bool) -> Option<(i32, i32)> {
+        for _ in 0..MAX_RANDOM_PLACEMENT_ATTEMPTS {
+            let position = randomizer.get_random_position_on_grid(grid);
+
+            if is_free(position) {
+                return Some(position);
             }
         }
+
+        grid.cells().find(|&position| is_free(position))
     }
 
+    // A single player game ends with its snake, a match once at most one snake is left.
     pub fn is_over(&self) -> bool {
-        self.snake.is_self_collision()
-            || self.obstacles.iter().any(|obstacle| obstacle.occupies(self.snake.get_head_position()))
+        let alive = self.players.iter().filter(|player| player.is_alive()).count();
+        alive < self.players.len().min(2)
     }
 
-    fn update_snake_position(&mut self, direction: Direction) {
-        let grid_size = self.options.grid_size;
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.collision_reason(0)
+    }
 
-        let (head_x, head_y) = self.snake.get_head_position();
-        let unit = 1;
-    
-        let mut new_head = match direction {
-            Direction::Up => (head_x, head_y + unit),
-            Direction::Down => (head_x, head_y - unit),
-            Direction::Left => (head_x - unit, head_y),
-            Direction::Right => (head_x + unit, head_y),
-        };
+    fn collision_reason(&self, index: usize) -> Option<GameOverReason> {
+        let snake = &self.players[index].snake;
+        let head = snake.get_head_position();
 
-        new_head.0 = (new_head.0 + grid_size) % grid_size;
-        new_head.1 = (new_head.1 + grid_size) % grid_size;
-    
-        self.snake.move_to(new_head);
+        if self.is_outside_grid(head) {
+            return Some(GameOverReason::Wall);
+        }
+
+        if snake.is_self_collision() {
+            return Some(GameOverReason::SelfCollision);
+        }
+
+        if self.obstacles.iter().any(|obstacle| obstacle.occupies(head)) {
+            return Some(GameOverReason::Obstacle);
+        }
+
+        let others = self.players.iter().enumerate()
+            .filter(|&(other, player)| other != index && player.is_alive())
+            .map(|(_, player)| &player.snake);
+
+        for other in others {
+            if other.head_overlaps(head) {
+                return Some(GameOverReason::HeadToHead);
+            }
+
+            if other.occupies(head) {
+                return Some(GameOverReason::HeadToBody);
+            }
+        }
+
+        None
+    }
+
+    fn is_outside_grid(&self, position: (i32, i32)) -> bool {
+        !self.options.grid().contains(position)
+    }
+
+    fn update_snake_position(&mut self, index: usize) {
+        let grid = self.options.grid();
+        let player = &mut self.players[index];
+
+        let (head_x, head_y) = player.snake.get_head_position();
+        let (dx, dy) = player.next_direction().offset();
+        let new_head = (head_x + dx, head_y + dy);
+
+        // Crossing a solid edge leaves the head outside the grid, which `is_over` reports.
+        let new_head = self.options.boundary_mode
+            .normalize(new_head, grid)
+            .unwrap_or(new_head);
+
+        player.snake.move_to(new_head);
     }
 
     pub fn update(&mut self) -> GameResult {
         let mut game_result = GameResult::Noop;
+        self.ticks += 1;
+        self.update_speed_effect();
 
-        self.update_snake_position(self.direction);
+        // Every snake moves before any collision is checked, so head-on crashes hit both.
+        for index in 0..self.players.len() {
+            if self.players[index].is_alive() {
+                self.update_snake_position(index);
+                self.players[index].stats_mut().ticks += 1;
+            }
+        }
 
-        let food_positions: Vec<_> = self.foods.iter().map(|food| food.position).collect();
+        let mut poisoned = vec![];
+
+        for index in 0..self.players.len() {
+            match self.players[index].is_alive().then(|| self.eat_food(index)).flatten() {
+                Some(FoodKind::Poison) => poisoned.push(index),
+                Some(_) => game_result = GameResult::Score,
+                None => {}
+            }
+        }
+
+        self.expire_foods();
+        self.level_up();
+
+        let deaths: Vec<_> = (0..self.players.len())
+            .filter(|&index| self.players[index].is_alive())
+            .filter_map(|index| poisoned.contains(&index).then_some(GameOverReason::Poisoned)
+                .or_else(|| self.collision_reason(index))
+                .map(|reason| (index, reason)))
+            .collect();
 
-        for food in &mut self.foods {
-            if self.snake.head_overlaps(food.position) {
-                
-                self.snake.grow();
-                food.position = Self::get_free_position(
-                    &mut self.randomizer,
-                    &self.snake, 
-                    &self.obstacles,
-                    &food_positions,
-                    self.options.grid_size);
+        let is_match = self.players.len() > 1;
 
-                game_result = GameResult::Score;
+        for &(index, reason) in &deaths {
+            let player = &mut self.players[index];
+            player.kill();
 
-                break;
+            if is_match {
+                self.events.push(GameEvent::PlayerDied { player: index, reason, stats: player.stats().clone() });
             }
         }
 
-        if self.is_over() {
-            game_result = GameResult::Over;
+        if deaths.is_empty() || !self.is_over() {
+            return game_result;
         }
 
-        game_result
+        if !is_match {
+            let (_, reason) = deaths[0];
+            self.events.push(GameEvent::GameOver { reason, stats: self.players[0].stats().clone() });
+        }
+        else {
+            self.events.push(GameEvent::MatchOver {
+                winner: self.winner(&deaths),
+                players: self.players.iter().map(|player| player.stats().clone()).collect()
+            });
+        }
+
+        GameResult::Over
     }
 
-    pub fn apply_options_and_reset(&mut self, options: GameOptions) {
-        debug!("apply_options_and_reset");
+    // Returns the kind of food the player ate, if any. Poison is left to `update`, which kills the player.
+    fn eat_food(&mut self, index: usize) -> Option<FoodKind> {
+        let player = &mut self.players[index];
+        let food_index = self.foods.iter().position(|food| player.snake.head_overlaps(food.position))?;
+        let food = &self.foods[food_index];
+        let kind = food.kind;
+
+        match kind.growth() {
+            growth if growth > 0 => (0..growth).for_each(|_| player.snake.grow()),
+            growth => player.snake.shrink(growth.unsigned_abs() as usize, SHRINK_MIN_LENGTH),
+        }
+
+        let length = player.snake.length();
+        player.stats_mut().record_food(kind, length);
+        self.events.push(GameEvent::FoodEaten {
+            player: index,
+            kind,
+            position: food.position,
+            stats: player.stats().clone()
+        });
 
-        if self.options.difficulty != options.difficulty {
-            if options.difficulty == Difficulty::Hard {
-                self.create_obstacles(2);
+        if let Some(multiplier) = kind.speed_multiplier() {
+            self.speed_effect = Some(SpeedEffect { multiplier, ticks_left: SPEED_EFFECT_TICKS });
+        }
+
+        self.respawn_food(food_index);
+
+        Some(kind)
+    }
+
+    // Food that runs out of time disappears and comes back somewhere else.
+    fn expire_foods(&mut self) {
+        let mut index = 0;
+
+        while index < self.foods.len() {
+            let food = &mut self.foods[index];
+
+            if !food.tick() {
+                index += 1;
+                continue;
             }
-            else {
-                self.obstacles = vec![];
+
+            self.events.push(GameEvent::FoodExpired { kind: food.kind, position: food.position });
+
+            if self.respawn_food(index) {
+                index += 1;
             }
         }
+    }
+
+    // Moves the food to a free cell as a newly picked kind. Returns false when the board
+    // is full and the food was removed instead.
+    fn respawn_food(&mut self, food_index: usize) -> bool {
+        let food_positions: Vec<_> = self.foods.iter().map(|food| food.position).collect();
+        let kind = self.next_food_kind();
 
+        let position = Self::get_free_position(
+            &mut self.randomizer,
+            &self.players,
+            &self.obstacles,
+            &food_positions,
+            self.map.as_ref(),
+            self.options.grid());
+
+        match position {
+            Some(position) => {
+                let color = self.foods[food_index].color();
+                self.foods[food_index] = Food::of_kind(kind, color, position);
+                true
+            }
+            // The board is full, so there is nowhere left to put it.
+            None => {
+                self.foods.remove(food_index);
+                false
+            }
+        }
+    }
+
+    // Draws a kind by its weight. With a single kind nothing is drawn, so games with only
+    // regular food use the randomizer exactly as they did before food kinds existed.
+    fn next_food_kind(&mut self) -> FoodKind {
+        let weights = &self.options.food_weights;
+
+        if let Some(kind) = weights.single_kind() {
+            return kind;
+        }
+
+        let roll = self.randomizer.get_random_index(weights.total() as usize);
+        weights.kind_at(roll as u32)
+    }
+
+    fn update_speed_effect(&mut self) {
+        if let Some(effect) = self.speed_effect.as_mut() {
+            effect.ticks_left = effect.ticks_left.saturating_sub(1);
+
+            if effect.ticks_left == 0 {
+                self.speed_effect = None;
+            }
+        }
+    }
+
+    // How much faster than the configured fps the game should currently run.
+    pub fn speed_multiplier(&self) -> f64 {
+        let level_speed = self.current_level().map_or(1.0, |level| level.speed);
+        level_speed * self.speed_effect.map_or(1.0, |effect| effect.multiplier)
+    }
+
+    // Levels are numbered from 1, level 0 is the game as the options set it up.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    fn current_level(&self) -> Option<&Level> {
+        self.level.checked_sub(1).and_then(|index| self.options.levels.get(index as usize))
+    }
+
+    // Climbs every level whose score the best player has reached, one event per level.
+    fn level_up(&mut self) {
+        let best_score = self.players.iter().map(|player| player.stats().score).max().unwrap_or_default();
+
+        while let Some(level) = self.options.levels.get(self.level as usize).filter(|level| best_score >= level.score).cloned() {
+            self.level += 1;
+            self.events.push(GameEvent::LevelUp { level: self.level });
+
+            for _ in 0..level.obstacles {
+                self.spawn_level_obstacle();
+            }
+
+            if let Some(food_count) = level.food_count {
+                self.set_food_count(food_count as usize);
+            }
+        }
+    }
+
+    // A single cell obstacle on a free cell, kept away from the heads so nobody gets one dropped in front of them.
+    fn spawn_level_obstacle(&mut self) {
+        let reach = LEVEL_OBSTACLE_CLEARANCE;
+        let taken: Vec<_> = self.players.iter()
+            .filter(|player| player.is_alive())
+            .map(|player| player.snake.get_head_position())
+            .flat_map(|(x, y)| (-reach..=reach).flat_map(move |dx| (-reach..=reach).map(move |dy| (x + dx, y + dy))))
+            .chain(self.foods.iter().map(|food| food.position))
+            .collect();
+
+        let position = Self::get_free_position(
+            &mut self.randomizer,
+            &self.players,
+            &self.obstacles,
+            &taken,
+            self.map.as_ref(),
+            self.options.grid());
+
+        if let Some(position) = position {
+            let obstacle = Obstacle::from_cells(OBSTACLE_COLOR, [position]);
+            self.events.push(GameEvent::ObstacleSpawned { position, cells: vec![position] });
+            self.obstacles.push(obstacle);
+        }
+    }
+
+    fn set_food_count(&mut self, food_count: usize) {
+        self.foods.truncate(food_count);
+
+        while self.foods.len() < food_count {
+            let taken: Vec<_> = self.foods.iter().map(|food| food.position).collect();
+
+            match self.spawn_food(&taken) {
+                Some(food) => self.foods.push(food),
+                None => break,
+            }
+        }
+    }
+
+    fn spawn_food(&mut self, taken: &[(i32, i32)]) -> Option<Food> {
+        let kind = self.next_food_kind();
+        let color = self.randomizer.get_random_color();
+        let position = Self::get_free_position(
+            &mut self.randomizer,
+            &self.players,
+            &self.obstacles,
+            taken,
+            self.map.as_ref(),
+            self.options.grid())?;
+
+        Some(Food::of_kind(kind, color, position))
+    }
+
+    // The last snake standing wins. When the remaining snakes crash on the same tick,
+    // the best score among them wins and a tie leaves the match without a winner.
+    fn winner(&self, deaths: &[(usize, GameOverReason)]) -> Option<usize> {
+        if let Some(index) = self.players.iter().position(Player::is_alive) {
+            return Some(index);
+        }
+
+        let score = |index: usize| self.players[index].stats().score;
+        let best = deaths.iter().map(|&(index, _)| score(index)).max()?;
+        let mut leaders = deaths.iter().filter(|&&(index, _)| score(index) == best);
+
+        match (leaders.next(), leaders.next()) {
+            (Some(&(index, _)), None) => Some(index),
+            _ => None,
+        }
+    }
+
+    // Leaves the game untouched when the options are rejected.
+    pub fn apply_options_and_reset(&mut self, options: GameOptions) -> Result<(), GameError> {
+        debug!("apply_options_and_reset");
+
+        let options = options.normalized();
+        let colors = Self::parse_colors(&options)?;
+        Self::check_key_bindings(&options)?;
+        self.base_options = options.clone();
         self.options = options;
 
-        self.cell_size = 2.0 / self.options.grid_size as f32;
+        // A loaded map owns the board layout, so it overrides the matching options. Its grid is
+        // kept in range like the options' own, and the food has to fit on it.
+        if let Some(map) = &self.map {
+            self.options.grid_width = map.grid_width.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
+            self.options.grid_height = map.grid_height.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
+            self.options.boundary_mode = map.boundary_mode;
+            self.options.food_count = self.options.food_count.min(GameOptions::max_food_count(self.options.grid()));
+        }
 
-        let color = self.options.snake_color.parse::<Color>().unwrap();
-        self.snake.set_color(color.to_array());
-        self.snake.resize(self.cell_size);
+        self.players = self.create_players();
+        self.set_player_colors(&colors);
 
         self.reset();
+
+        Ok(())
     }
 
     pub fn reset(&mut self) {
-        self.direction = Direction::Right;
-        self.snake.reset();
-        self.foods = (0..self.options.food_count).map(|_| Food::new(
-            self.randomizer.get_random_color(),
-            Self::get_free_position(
-                &mut self.randomizer,
-                &self.snake,
-                &vec![],
-                &vec![],
-                self.options.grid_size),
-            self.cell_size)).collect();
+        for player in &mut self.players {
+            player.reset();
+        }
+
+        self.ticks = 0;
+        self.speed_effect = None;
+        self.level = 0;
+        self.events.clear();
+
+        self.seed = self.options.seed.unwrap_or_else(|| self.randomizer.next_seed());
+        self.randomizer.reseed(self.seed);
+
+        self.spawn_obstacles();
+
+        self.foods = vec![];
+        self.set_food_count(self.options.food_count as usize);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
-    pub fn change_direction(&mut self, direction: Direction) {
-        if !matches!(
-            (self.direction, direction),
-            (Direction::Up, Direction::Down) 
-            | (Direction::Down, Direction::Up)
-            | (Direction::Left, Direction::Right)
-            | (Direction::Right, Direction::Left)
-        ) {
-            self.direction = direction;
+    pub fn tick(&self) -> u32 {
+        self.ticks
+    }
+
+    // Stats of the first player, which is the only one outside of multiplayer.
+    pub fn stats(&self) -> &GameStats {
+        self.players[0].stats()
+    }
+
+    pub fn set_ai_playing(&mut self, is_ai: bool) {
+        self.ai_playing = is_ai;
+
+        for player in &mut self.players {
+            player.set_ai_playing(is_ai);
         }
     }
 
-    pub fn get_vertices(&self) -> Vec<f32> {
-        let mut all_vertices = Vec::new();
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            tick: self.ticks,
+            players: self.players.iter().map(Player::snapshot).collect(),
+            foods: self.foods.iter().map(|food| FoodSnapshot {
+                position: food.position,
+                kind: food.kind,
+                color: food.color(),
+                lifetime: food.lifetime,
+            }).collect(),
+            obstacles: self.obstacles.iter().map(|obstacle| obstacle.cells().collect()).collect(),
+            speed_effect: self.speed_effect,
+            level: self.level,
+        }
+    }
 
-        all_vertices.extend_from_slice(&self.snake.as_vertices());
+    // Puts the board into the state captured by `snapshot`, keeping the options and the randomizer.
+    pub fn restore(&mut self, snapshot: &GameSnapshot) {
+        self.ticks = snapshot.tick;
+        self.speed_effect = snapshot.speed_effect;
+        self.level = snapshot.level;
 
-        for food in &self.foods {
-            all_vertices.extend_from_slice(&food.as_vertices());
+        for (player, player_snapshot) in self.players.iter_mut().zip(&snapshot.players) {
+            player.restore(player_snapshot);
         }
 
-        for obstacle in &self.obstacles {
-            all_vertices.extend_from_slice(&obstacle.as_vertices());
+        self.foods = snapshot.foods.iter().map(|food_snapshot| {
+            let mut food = Food::of_kind(food_snapshot.kind, food_snapshot.color, food_snapshot.position);
+            food.lifetime = food_snapshot.lifetime.or(food.lifetime);
+            food
+        }).collect();
+
+        self.obstacles = snapshot.obstacles.iter().map(|cells| Obstacle::from_cells(
+            OBSTACLE_COLOR,
+            cells.iter().copied())).collect();
+    }
+
+    pub fn save(&self) -> SavedGame {
+        SavedGame {
+            version: SAVE_VERSION,
+            options: self.base_options.clone(),
+            map: self.map.clone(),
+            seed: self.seed,
+            randomizer: self.randomizer.state(),
+            snapshot: self.snapshot(),
+            // The game does not know who is playing, the orchestrator fills it in.
+            state: GameState::UserPlaying,
+        }
+    }
+
+    // Carries on with a saved game. Saves that do not fit their own options are rejected
+    // before anything changes, so a bad one leaves the current game as it was.
+    pub fn load(&mut self, saved: &SavedGame) -> Result<(), GameError> {
+        if !saved.is_supported() {
+            return Err(GameError::UnsupportedSave(saved.version));
+        }
+
+        if let Some(map) = &saved.map {
+            map.validate()?;
+        }
+
+        Self::parse_colors(&saved.options)?;
+        Self::check_save(saved)?;
+
+        self.map = saved.map.clone();
+        self.apply_options_and_reset(saved.options.clone())?;
+        self.seed = saved.seed;
+        self.restore(&saved.snapshot);
+        self.events.clear();
+
+        if let Some(state) = saved.randomizer {
+            self.randomizer.reseed(state);
+        }
+
+        Ok(())
+    }
+
+    fn check_save(saved: &SavedGame) -> Result<(), GameError> {
+        let options = saved.options.clone().normalized();
+        let grid = saved.map.as_ref().map_or(options.grid(), GameMap::grid);
+        let snapshot = &saved.snapshot;
+
+        if snapshot.players.len() != options.player_options().len() {
+            return Err(GameError::InvalidSave(format!("expected {} players, got {}", options.player_options().len(), snapshot.players.len())));
+        }
+
+        if snapshot.players.iter().any(|player| player.body.is_empty()) {
+            return Err(GameError::InvalidSave("every snake needs a body".into()));
+        }
+
+        let mut cells = snapshot.players.iter().flat_map(|player| player.body.iter())
+            .chain(snapshot.foods.iter().map(|food| &food.position))
+            .chain(snapshot.obstacles.iter().flatten());
+
+        if let Some(cell) = cells.find(|&&cell| !grid.contains(cell)) {
+            return Err(GameError::InvalidSave(format!("{:?} is outside the {}x{} grid", cell, grid.width, grid.height)));
+        }
+
+        Ok(())
+    }
+
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn change_direction(&mut self, direction: Direction) -> bool {
+        self.change_player_direction(0, direction)
+    }
+
+    // Queues the turn for the player's next free tick. Returns whether the snake will now head somewhere new.
+    pub fn change_player_direction(&mut self, index: usize, direction: Direction) -> bool {
+        let depth = self.options.input_buffer;
+        self.players.get_mut(index).is_some_and(|player| player.queue_turn(direction, depth))
+    }
+
+    // Adds a cell to `instances` for everything on the board, so the caller can keep reusing one buffer.
+    // `alpha` is how far the snakes have got from their last cells to the current ones.
+    pub fn collect_instances(&self, alpha: f32, instances: &mut Vec<CellInstance>) {
+        let cell = |position: (i32, i32)| (position.0 as f32, position.1 as f32);
+
+        // Crashed snakes leave the board in a match, a lone snake stays to show where it ended.
+        for player in &self.players {
+            if player.is_alive() || self.players.len() == 1 {
+                let color = player.snake.color();
+                instances.extend(player.snake.interpolated_cells(alpha)
+                    .map(|position| CellInstance { position, color, kind: CellKind::Snake }));
+            }
         }
-        
 
-        all_vertices
+        instances.extend(self.foods.iter()
+            .map(|food| CellInstance { position: cell(food.position), color: food.display_color(), kind: CellKind::Food }));
+
+        for obstacle in &self.obstacles {
+            let color = obstacle.color();
+            instances.extend(obstacle.cells()
+                .map(|position| CellInstance { position: cell(position), color, kind: CellKind::Obstacle }));
+        }
     }
 
 }
@@ -200,27 +809,23 @@ impl<R: Randomizer> Game< R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{models::{GameOptions, Difficulty}, randomizer::OsRandomizer};
+    use crate::{abstractions::AiController, ai::GreedyBfsAi, map::{ObstacleShape, Rect, Spawn, Wall}, models::{AiStrategy, BoundaryMode, FoodKind, GameOptions, Difficulty, KeyBindings, PlayerController, PlayerOptions, FoodWeights, Level, MAX_PLAYERS, MIN_GRID_SIZE}, randomizer::{OsRandomizer, SeededRandomizer}};
 
     fn default_game_options() -> GameOptions {
         GameOptions {
-            id: "".into(),
-            fps: 10,
-            frame_threshold_ms: 10.0,
-            grid_size: 10,
-            food_count: 3,
-            difficulty: Difficulty::Easy,
-            snake_color: "#00FF00".to_string(),
+            grid_width: 10,
+            grid_height: 10,
+            ..GameOptions::test_default()
         }
     }
 
-    fn setup_game(difficulty: Difficulty) -> Game<OsRandomizer> {
-        let randomizer = OsRandomizer::new();
+    fn setup_game(difficulty: Difficulty) -> Game<SeededRandomizer> {
+        let randomizer = SeededRandomizer::new(0);
         let mut options = default_game_options();
         options.difficulty = difficulty;
     
         let mut game = Game::new(options, randomizer);
-        game.initialize();
+        game.initialize().unwrap();
         game
     }
 
@@ -236,13 +841,13 @@ mod tests {
     fn test_snake_moves() {
         let mut game = setup_game(Difficulty::Easy);
 
-        let initial_head_position = game.snake.get_head_position();
+        let initial_head_position = game.players[0].snake.get_head_position();
         game.change_direction(Direction::Right);
         game.update();
-        let new_head_position = game.snake.get_head_position();
+        let new_head_position = game.players[0].snake.get_head_position();
 
         assert_ne!(initial_head_position, new_head_position, "Snake should move when updated");
-        assert_eq!(new_head_position.0, (initial_head_position.0 + 1) % game.options.grid_size, "Snake should move correctly to the right");
+        assert_eq!(new_head_position.0, (initial_head_position.0 + 1) % game.options.grid_width, "Snake should move correctly to the right");
     }
 
     #[test]
@@ -251,7 +856,7 @@ mod tests {
 
         let food_position = game.foods[0].position;
         let position = (food_position.0 - 1, food_position.1);
-        game.snake.move_to(position);
+        game.players[0].snake.move_to(position);
 
         let result = game.update();
 
@@ -263,13 +868,13 @@ mod tests {
     fn test_game_over_on_self_collision() {
         let mut game = setup_game(Difficulty::Easy);
 
-        game.snake.grow();
-        game.snake.grow();
-        game.snake.move_to((1, 1));
-        game.snake.move_to((1, 2));
-        game.snake.move_to((2, 2));
-        game.snake.move_to((2, 1));
-        game.snake.move_to((1, 1)); // Colliding with itself
+        game.players[0].snake.grow();
+        game.players[0].snake.grow();
+        game.players[0].snake.move_to((1, 1));
+        game.players[0].snake.move_to((1, 2));
+        game.players[0].snake.move_to((2, 2));
+        game.players[0].snake.move_to((2, 1));
+        game.players[0].snake.move_to((1, 1)); // Colliding with itself
 
         let result = game.update();
         assert_eq!(result, GameResult::Over, "Game should be over if the snake collides with itself");
@@ -281,23 +886,653 @@ mod tests {
 
         assert!(!game.obstacles.is_empty(), "Obstacles should be generated in Hard mode");
 
-        let obstacle_pos = game.obstacles[0].position;
+        let obstacle_pos = game.obstacles[0].position();
         let position = (obstacle_pos.0 - 1, obstacle_pos.1);
-        game.snake.move_to(position);
+        game.players[0].snake.move_to(position);
 
         let result = game.update();
         assert_eq!(result, GameResult::Over, "Game should be over if the snake hits an obstacle");
     }
 
+    #[test]
+    fn test_random_obstacles_fit_on_free_cells() {
+        let mut options = default_game_options();
+        options.difficulty = Difficulty::Hard;
+        options.grid_width = MIN_GRID_SIZE;
+        options.grid_height = MIN_GRID_SIZE;
+
+        for seed in 0..50 {
+            options.seed = Some(seed);
+            let mut game = Game::new(options.clone(), SeededRandomizer::new(0));
+            game.reset();
+
+            let cells: Vec<_> = game.obstacles.iter().flat_map(Obstacle::cells).collect();
+
+            assert_eq!(cells.len(), 8, "Seed {} should place two whole 2x2 blocks", seed);
+            assert!(cells.iter().all(|&cell| game.options.grid().contains(cell)), "Seed {} put an obstacle over the edge", seed);
+            assert!(!cells.iter().any(|&cell| game.players[0].snake.occupies(cell)), "Seed {} put an obstacle on the snake", seed);
+        }
+    }
+
     #[test]
     fn test_change_direction() {
         let mut game = setup_game(Difficulty::Easy);
 
         game.change_direction(Direction::Up);
-        assert_eq!(game.direction, Direction::Up, "Snake should be able to change direction");
+        game.update();
+        assert_eq!(game.players[0].direction, Direction::Up, "Snake should be able to change direction");
 
         game.change_direction(Direction::Down);
-        assert_eq!(game.direction, Direction::Up, "Snake should not be able to reverse direction immediately");
+        game.update();
+        assert_eq!(game.players[0].direction, Direction::Up, "Snake should not be able to reverse direction immediately");
+    }
+
+    #[test]
+    fn test_quick_turns_take_one_tick_each() {
+        let mut game = setup_game(Difficulty::Easy);
+        game.foods.clear();
+        game.players[0].snake.move_to((5, 5));
+
+        assert!(game.change_direction(Direction::Up));
+        assert!(game.change_direction(Direction::Left), "Turning again within the tick should be queued");
+        assert!(!game.change_direction(Direction::Right), "Reversing the queued turn should be refused");
+
+        game.update();
+        assert_eq!(game.players[0].snake.get_head_position(), (5, 6), "The first turn should be taken first");
+        game.update();
+        assert_eq!(game.players[0].snake.get_head_position(), (4, 6), "The second turn should follow on the next tick");
+        assert!(!game.is_over(), "A quick U-turn should not run the snake into itself");
+    }
+
+    #[test]
+    fn test_input_buffer_depth() {
+        let mut options = default_game_options();
+        options.input_buffer = 1;
+        let mut game = Game::new(options, OsRandomizer::new());
+        game.initialize().unwrap();
+
+        assert!(game.change_direction(Direction::Up));
+        assert!(!game.change_direction(Direction::Left), "A full buffer should drop further turns");
+
+        game.update();
+        assert!(game.change_direction(Direction::Left), "Taking a turn should free up the buffer");
+    }
+
+    #[test]
+    fn test_wraps_around_edge() {
+        let mut game = setup_game(Difficulty::Easy);
+
+        game.players[0].snake.move_to((9, 5));
+        let result = game.update();
+
+        assert_ne!(result, GameResult::Over, "Wrapping edges should not end the game");
+        assert_eq!(game.players[0].snake.get_head_position(), (0, 5), "Snake should reappear on the opposite edge");
+    }
+
+    #[test]
+    fn test_solid_wall_ends_game() {
+        let mut game = setup_game(Difficulty::Easy);
+        game.options.boundary_mode = BoundaryMode::Solid;
+
+        game.players[0].snake.move_to((9, 5));
+        let result = game.update();
+
+        assert_eq!(result, GameResult::Over, "Hitting a solid wall should end the game");
+    }
+
+    #[test]
+    fn test_mixed_boundary_mode() {
+        let mut game = setup_game(Difficulty::Easy);
+        game.options.boundary_mode = BoundaryMode::WrapVertical;
+
+        game.players[0].snake.move_to((5, 9));
+        game.change_direction(Direction::Up);
+        assert_ne!(game.update(), GameResult::Over, "Vertical edges should wrap");
+        assert_eq!(game.players[0].snake.get_head_position(), (5, 0));
+
+        game.players[0].snake.move_to((9, 5));
+        game.change_direction(Direction::Right);
+        assert_eq!(game.update(), GameResult::Over, "Horizontal edges should be solid");
+    }
+
+    #[test]
+    fn test_wraps_each_side_of_a_rectangular_grid() {
+        let mut options = default_game_options();
+        options.grid_width = 24;
+        options.grid_height = 12;
+        let mut game = Game::new(options, OsRandomizer::new());
+        game.initialize().unwrap();
+        game.obstacles.clear();
+        game.foods.clear();
+
+        game.players[0].snake.move_to((23, 5));
+        assert_ne!(game.update(), GameResult::Over);
+        assert_eq!(game.players[0].snake.get_head_position(), (0, 5), "Columns should wrap at the width");
+
+        game.players[0].snake.move_to((5, 11));
+        game.change_direction(Direction::Up);
+        assert_ne!(game.update(), GameResult::Over);
+        assert_eq!(game.players[0].snake.get_head_position(), (5, 0), "Rows should wrap at the height");
+
+        assert!(game.is_outside_grid((5, 12)), "Rows past the height are off the board");
+    }
+
+    #[test]
+    fn test_collects_a_cell_instance_per_cell() {
+        let mut game = setup_game(Difficulty::Hard);
+        game.obstacles = vec![Obstacle::from_cells(OBSTACLE_COLOR, [(1, 1), (1, 2)])];
+        let mut instances = vec![];
+
+        game.collect_instances(1.0, &mut instances);
+
+        let count = |kind| instances.iter().filter(|instance| instance.kind == kind).count();
+        assert_eq!(count(CellKind::Snake), game.players[0].snake.length());
+        assert_eq!(count(CellKind::Food), game.foods.len());
+        assert_eq!(count(CellKind::Obstacle), 2);
+
+        let head = game.players[0].snake.get_head_position();
+        assert_eq!(instances[0].position, (head.0 as f32, head.1 as f32), "The head is drawn first");
+        assert_eq!(instances[0].color, game.players[0].snake.color());
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_board() {
+        let mut options = default_game_options();
+        options.difficulty = Difficulty::Hard;
+        options.seed = Some(1234);
+
+        let mut first = Game::new(options.clone(), SeededRandomizer::new(1));
+        let mut second = Game::new(options, SeededRandomizer::new(2));
+        first.initialize().unwrap();
+        second.initialize().unwrap();
+        first.reset();
+        second.reset();
+
+        let positions = |game: &Game<SeededRandomizer>| game.foods.iter().map(|food| food.position).collect::<Vec<_>>();
+        let obstacles = |game: &Game<SeededRandomizer>| game.obstacles.iter().map(|obstacle| obstacle.position()).collect::<Vec<_>>();
+
+        assert_eq!(positions(&first), positions(&second), "Seeded games should place food identically");
+        assert_eq!(obstacles(&first), obstacles(&second), "Seeded games should place obstacles identically");
+    }
+
+    #[test]
+    fn test_saved_game_plays_on_identically() {
+        let ai = GreedyBfsAi::new();
+        let play = |game: &mut Game<SeededRandomizer>, ticks: u32| {
+            for _ in 0..ticks {
+                let player = &game.players[0];
+                let obstacles = game.obstacles_for(0);
+
+                if let Some(direction) = ai.get_direction(&player.snake, &game.foods, &obstacles, game.options.grid(), game.options.boundary_mode) {
+                    game.change_direction(direction);
+                }
+
+                game.update();
+            }
+        };
+
+        let mut options = default_game_options();
+        options.grid_width = 20;
+        options.grid_height = 20;
+        options.difficulty = Difficulty::Hard;
+
+        let mut original = Game::new(options, SeededRandomizer::new(8));
+        original.initialize().unwrap();
+        original.reset();
+        play(&mut original, 30);
+
+        let json = serde_json::to_string(&original.save()).unwrap();
+        let saved: SavedGame = serde_json::from_str(&json).unwrap();
+
+        let mut loaded = Game::new(default_game_options(), SeededRandomizer::new(99));
+        loaded.initialize().unwrap();
+        loaded.load(&saved).unwrap();
+        assert_eq!(loaded.snapshot(), original.snapshot());
+
+        play(&mut original, 60);
+        play(&mut loaded, 60);
+        assert!(original.stats().score > 1, "The snake should have eaten food spawned after the save");
+        assert_eq!(loaded.snapshot(), original.snapshot(), "Food spawned after loading should match the original game");
+    }
+
+    #[test]
+    fn test_rejects_saves_that_do_not_fit() {
+        let mut game = setup_game(Difficulty::Easy);
+        let mut saved = game.save();
+        saved.snapshot.foods[0].position = (50, 50);
+
+        assert!(matches!(game.load(&saved), Err(GameError::InvalidSave(_))));
+
+        let mut saved = game.save();
+        saved.snapshot.obstacles.push(vec![(9, 9), (10, 9)]);
+
+        assert!(matches!(game.load(&saved), Err(GameError::InvalidSave(_))), "Obstacles have to be on the board too");
+
+        saved.version = SAVE_VERSION + 1;
+        assert_eq!(game.load(&saved), Err(GameError::UnsupportedSave(SAVE_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_stats_track_food_and_length() {
+        let mut game = setup_game(Difficulty::Easy);
+        game.reset();
+        let initial_length = game.stats().length;
+
+        let food_position = game.foods[0].position;
+        game.players[0].snake.move_to((food_position.0 - 1, food_position.1));
+        game.change_direction(Direction::Right);
+        game.update();
+
+        let stats = game.stats();
+        assert_eq!(stats.score, 1, "Eating food should increase the score");
+        assert_eq!(stats.ticks, 1, "Each update should count as a tick");
+        assert_eq!(stats.length, initial_length + 1, "Length should follow the snake");
+        assert_eq!(stats.max_length, initial_length + 1);
+        assert_eq!(stats.foods_eaten.get(&FoodKind::Regular), Some(&1));
+
+        game.reset();
+        assert_eq!(game.stats().score, 0, "Reset should clear the stats");
+        assert_eq!(game.stats().length, initial_length);
+    }
+
+    #[test]
+    fn test_emits_game_events() {
+        let mut game = setup_game(Difficulty::Hard);
+        game.reset();
+
+        let spawned = game.drain_events().iter()
+            .filter(|event| matches!(event, GameEvent::ObstacleSpawned { .. }))
+            .count();
+        assert_eq!(spawned, game.obstacles.len(), "Each obstacle should be announced");
+
+        game.obstacles.clear();
+        game.foods.truncate(1);
+        let food_position = game.foods[0].position;
+        game.players[0].snake.move_to((food_position.0 - 1, food_position.1));
+        game.update();
+
+        let events = game.drain_events();
+        assert!(matches!(events.as_slice(), [GameEvent::FoodEaten { position, .. }] if *position == food_position));
+        assert!(game.drain_events().is_empty(), "Draining should empty the queue");
+
+        game.options.boundary_mode = BoundaryMode::Solid;
+        game.players[0].snake.move_to((game.options.grid_width - 1, 5));
+        game.update();
+
+        let events = game.drain_events();
+        assert!(matches!(events.last(), Some(GameEvent::GameOver { reason: GameOverReason::Wall, .. })));
+    }
+
+    #[test]
+    fn test_load_map() {
+        let mut game = setup_game(Difficulty::Hard);
+        let map = GameMap {
+            name: "corridor".into(),
+            grid_width: 12,
+            grid_height: 12,
+            boundary_mode: BoundaryMode::Solid,
+            walls: vec![Wall { from: (0, 6), to: (11, 6) }],
+            obstacles: vec![ObstacleShape::Rects { rects: vec![
+                Rect { x: 2, y: 2, width: 1, height: 3 },
+                Rect { x: 3, y: 2, width: 2, height: 1 },
+            ] }],
+            spawn: Spawn { position: (6, 9), direction: Direction::Left, length: 3 },
+            food_zones: vec![Rect { x: 0, y: 0, width: 12, height: 2 }],
+        };
+
+        game.load_map(map).unwrap();
+
+        assert_eq!(game.options.grid(), GridSize::square(12), "Map should set the grid size");
+        assert_eq!(game.options.boundary_mode, BoundaryMode::Solid, "Map should set the boundary mode");
+        assert_eq!(game.players[0].direction, Direction::Left, "Snake should face the spawn direction");
+        assert_eq!(game.players[0].snake.get_head_position(), (6, 9), "Snake should start at the spawn position");
+        assert!(game.players[0].snake.occupies((8, 9)), "Body should trail behind the head");
+        assert_eq!(game.obstacles.len(), 2, "Walls and obstacles should both become obstacles");
+        assert!(game.obstacles.iter().any(|obstacle| obstacle.occupies((11, 6))));
+        assert!(game.obstacles.iter().any(|obstacle| obstacle.occupies((4, 2))), "L-shaped obstacle should cover both arms");
+        assert!(!game.obstacles.iter().any(|obstacle| obstacle.occupies((4, 3))), "L-shaped obstacle should leave its corner open");
+        assert!(game.foods.iter().all(|food| food.position.1 < 2), "Food should spawn inside the food zones");
+
+        game.apply_options_and_reset(default_game_options()).unwrap();
+        assert_eq!(game.options.grid(), GridSize::square(12), "Options should not override a loaded map");
+
+        game.unload_map().unwrap();
+        assert_eq!(game.options.grid(), GridSize::square(10), "Unloading should restore the options grid size");
+        assert_eq!(game.players[0].direction, Direction::Right);
+    }
+
+    #[test]
+    fn test_map_grid_stays_in_range() {
+        let mut game = setup_game(Difficulty::Easy);
+        let mut options = default_game_options();
+        options.grid_width = 40;
+        options.grid_height = 40;
+        options.food_count = 400;
+        game.apply_options_and_reset(options).unwrap();
+
+        game.load_map(GameMap {
+            name: "tiny".into(),
+            grid_width: 4,
+            grid_height: MAX_GRID_SIZE + 50,
+            boundary_mode: BoundaryMode::Wrap,
+            walls: vec![],
+            obstacles: vec![],
+            spawn: Spawn { position: (3, 3), direction: Direction::Right, length: 3 },
+            food_zones: vec![],
+        }).unwrap();
+
+        assert_eq!(game.options.grid(), GridSize::new(MIN_GRID_SIZE, MAX_GRID_SIZE), "A map that skipped validation should be pulled into range");
+        assert_eq!(game.options.food_count, GameOptions::max_food_count(game.options.grid()), "The food should fit on the map");
+        assert_eq!(game.foods.len(), game.options.food_count as usize);
+    }
+
+    #[test]
+    fn test_shaped_obstacle_collision() {
+        let mut game = setup_game(Difficulty::Easy);
+        game.set_obstacles(vec![Obstacle::from_cells(OBSTACLE_COLOR, [(6, 5), (6, 6), (7, 6)])]);
+        game.foods.clear();
+
+        game.players[0].snake.move_to((5, 6));
+        game.players[0].direction = Direction::Right;
+        game.update();
+        assert_eq!(game.game_over_reason(), Some(GameOverReason::Obstacle), "Snake should hit any cell of the shape");
+
+        game.reset();
+        game.set_obstacles(vec![Obstacle::from_cells(OBSTACLE_COLOR, [(6, 5), (6, 6), (7, 6)])]);
+        game.foods.clear();
+
+        game.players[0].snake.move_to((6, 7));
+        game.players[0].direction = Direction::Right;
+        game.update();
+        assert!(!game.is_over(), "Cells outside the shape should stay free");
+    }
+
+    fn setup_match() -> Game<OsRandomizer> {
+        let mut options = default_game_options();
+        options.players = vec![
+            PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys: KeyBindings::Wasd } },
+            PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Ai { strategy: AiStrategy::Greedy } },
+        ];
+
+        let mut game = Game::new(options, OsRandomizer::new());
+        game.initialize().unwrap();
+        game.reset();
+        game.foods.clear();
+        game
+    }
+
+    #[test]
+    fn test_players_spawn_apart() {
+        let game = setup_match();
+
+        assert_eq!(game.players.len(), 2);
+        assert_eq!(game.players[0].snake.get_head_position(), (7, 3));
+        assert_eq!(game.players[1].snake.get_head_position(), (7, 6));
+        assert!(!game.players[0].stats().is_ai, "Human players should not count as AI");
+        assert!(game.players[1].stats().is_ai, "AI players should count as AI");
+        assert!(game.obstacles_for(0).iter().any(|obstacle| obstacle.occupies((7, 6))), "Other snakes should be obstacles for the AI");
+        assert!(!game.obstacles_for(0).iter().any(|obstacle| obstacle.occupies((7, 3))));
+    }
+
+    #[test]
+    fn test_players_spawn_clear_of_map_walls() {
+        let mut game = setup_match();
+        let map = GameMap {
+            name: "split".into(),
+            grid_width: 12,
+            grid_height: 12,
+            boundary_mode: BoundaryMode::Solid,
+            walls: vec![Wall { from: (0, 8), to: (11, 8) }],
+            obstacles: vec![],
+            spawn: Spawn { position: (8, 7), direction: Direction::Right, length: 4 },
+            food_zones: vec![],
+        };
+
+        game.load_map(map).unwrap();
+
+        let first: Vec<(i32, i32)> = game.players[0].snake.body().to_vec();
+        let second: Vec<(i32, i32)> = game.players[1].snake.body().to_vec();
+        assert!(second.iter().all(|&cell| !game.obstacles.iter().any(|obstacle| obstacle.occupies(cell))), "Extra players should not start inside a wall");
+        assert!(second.iter().all(|cell| !first.contains(cell)), "Extra players should not start on the map's spawn");
+    }
+
+    #[test]
+    fn test_speed_food_changes_the_speed_of_every_snake() {
+        let mut game = setup_match();
+        game.foods = vec![Food::of_kind(FoodKind::SpeedUp, [1.0; 4], (8, 6))];
+
+        game.update();
+
+        assert_eq!(game.players[1].stats().foods_eaten.get(&FoodKind::SpeedUp), Some(&1));
+        assert!(game.speed_multiplier() > 1.0, "The whole game should speed up");
+        assert_eq!(game.players[0].snake.get_head_position(), (8, 3), "The other snake should still move one cell per tick");
+        assert_eq!(game.players[1].snake.get_head_position(), (8, 6));
+    }
+
+    #[test]
+    fn test_head_to_head_crash_is_a_draw() {
+        let mut game = setup_match();
+        game.players[0].snake.move_to((4, 5));
+        game.players[0].direction = Direction::Right;
+        game.players[1].snake.move_to((6, 5));
+        game.players[1].direction = Direction::Left;
+
+        assert_eq!(game.update(), GameResult::Over, "Match should end when both snakes crash");
+
+        let events = game.drain_events();
+        let deaths = events.iter()
+            .filter(|event| matches!(event, GameEvent::PlayerDied { reason: GameOverReason::HeadToHead, .. }))
+            .count();
+        assert_eq!(deaths, 2, "Both snakes should die head to head");
+        assert!(matches!(events.last(), Some(GameEvent::MatchOver { winner: None, players }) if players.len() == 2));
+    }
+
+    #[test]
+    fn test_head_to_body_crash_loses() {
+        let mut game = setup_match();
+        game.players[0].snake.move_to((5, 5));
+        game.players[0].direction = Direction::Up;
+
+        assert_eq!(game.update(), GameResult::Over);
+        assert!(!game.players[0].is_alive(), "Running into another snake should be fatal");
+        assert!(game.players[1].is_alive());
+
+        let events = game.drain_events();
+        assert!(events.contains(&GameEvent::PlayerDied {
+            player: 0,
+            reason: GameOverReason::HeadToBody,
+            stats: game.players[0].stats().clone()
+        }));
+        assert!(matches!(events.last(), Some(GameEvent::MatchOver { winner: Some(1), .. })));
+    }
+
+    #[test]
+    fn test_rejects_invalid_colors() {
+        let mut game = setup_game(Difficulty::Easy);
+        let mut options = default_game_options();
+        options.snake_color = "not a color".into();
+        options.grid_width = 40;
+
+        let error = game.apply_options_and_reset(options.clone()).unwrap_err();
+
+        assert_eq!(error, GameError::InvalidColor("not a color".into()));
+        assert_eq!(game.options().grid_width, 10, "Rejected options should not be applied");
+        assert!(Game::new(options, OsRandomizer::new()).initialize().is_err());
+    }
+
+    #[test]
+    fn test_rejects_players_sharing_keys() {
+        let mut game = setup_game(Difficulty::Easy);
+        let human = |keys| PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys } };
+        let mut options = default_game_options();
+        options.players = vec![human(KeyBindings::Wasd), human(KeyBindings::All)];
+
+        let error = game.apply_options_and_reset(options.clone()).unwrap_err();
+
+        assert_eq!(error, GameError::InvalidOptions("players[1].controller.keys share keys with players[0]".into()));
+        assert_eq!(game.players.len(), 1, "Rejected options should not be applied");
+
+        options.players[1] = human(KeyBindings::Arrows);
+        game.apply_options_and_reset(options).unwrap();
+        assert_eq!(game.players.len(), 2);
+    }
+
+    #[test]
+    fn test_spawns_inside_small_grids() {
+        let grids = [GridSize::square(MIN_GRID_SIZE), GridSize::new(MIN_GRID_SIZE, 40), GridSize::new(40, MIN_GRID_SIZE)];
+
+        for (grid, players) in grids.into_iter().flat_map(|grid| (1..=MAX_PLAYERS).map(move |players| (grid, players))) {
+            let mut options = default_game_options();
+            options.grid_width = grid.width;
+            options.grid_height = grid.height;
+            options.players = vec![PlayerOptions { color: "#00FF00".into(), controller: PlayerController::default() }; players];
+
+            let mut game = Game::new(options, SeededRandomizer::new(1));
+            game.initialize().unwrap();
+
+            let cells: Vec<(i32, i32)> = game.players.iter().flat_map(|player| player.snake.body().to_vec()).collect();
+            assert!(cells.iter().all(|&cell| grid.contains(cell)), "{} players should fit on {:?}", players, grid);
+            assert!(cells.iter().enumerate().all(|(index, cell)| !cells[index + 1..].contains(cell)), "{} players should not overlap on {:?}", players, grid);
+        }
+    }
+
+    #[test]
+    fn test_normalizes_options() {
+        let mut options = default_game_options();
+        options.grid_height = 0;
+
+        let game = Game::new(options, OsRandomizer::new());
+
+        assert_eq!(game.options().grid(), GridSize::new(10, MIN_GRID_SIZE), "A zero sized grid should not divide by zero");
+    }
+
+    // Puts a single food of `kind` right in front of the first snake.
+    fn place_food_ahead(game: &mut Game<impl Randomizer>, kind: FoodKind) {
+        let (x, y) = game.players[0].snake.get_head_position();
+        game.foods = vec![Food::of_kind(kind, [1.0; 4], ((x + 1) % game.options.grid_width, y))];
+    }
+
+    #[test]
+    fn test_food_kinds_apply_their_effects() {
+        let mut game = setup_game(Difficulty::Easy);
+        let length = game.players[0].snake.length();
+
+        place_food_ahead(&mut game, FoodKind::Bonus);
+        assert_eq!(game.update(), GameResult::Score);
+        assert_eq!(game.stats().score, FoodKind::Bonus.points());
+        assert_eq!(game.players[0].snake.length(), length + 1);
+
+        place_food_ahead(&mut game, FoodKind::Shrink);
+        game.update();
+        assert_eq!(game.players[0].snake.length(), length - 2, "Shrink food should take three segments off");
+
+        place_food_ahead(&mut game, FoodKind::SpeedUp);
+        game.update();
+        assert!(game.speed_multiplier() > 1.0);
+        assert_eq!(game.snapshot().speed_effect.map(|effect| effect.ticks_left), Some(SPEED_EFFECT_TICKS));
+
+        place_food_ahead(&mut game, FoodKind::SlowDown);
+        game.update();
+        assert!(game.speed_multiplier() < 1.0, "The latest speed food should win");
+
+        for _ in 0..SPEED_EFFECT_TICKS {
+            game.foods.clear();
+            game.update();
+        }
+        assert_eq!(game.speed_multiplier(), 1.0, "The speed effect should wear off");
+
+        game.drain_events();
+        place_food_ahead(&mut game, FoodKind::Poison);
+        assert_eq!(game.update(), GameResult::Over);
+        assert!(matches!(game.drain_events().last(), Some(GameEvent::GameOver { reason: GameOverReason::Poisoned, .. })));
+    }
+
+    #[test]
+    fn test_golden_food_expires() {
+        let mut options = default_game_options();
+        options.boundary_mode = BoundaryMode::Solid;
+        let mut game = Game::new(options, OsRandomizer::new());
+        game.initialize().unwrap();
+        game.reset();
+        game.drain_events();
+
+        // Far from the snake, which turns up before reaching the top edge.
+        game.foods = vec![Food::of_kind(FoodKind::Golden, [1.0; 4], (0, 0))];
+        game.foods[0].lifetime = Some(2);
+        game.change_direction(Direction::Up);
+
+        game.update();
+        assert!(game.drain_events().is_empty());
+
+        game.update();
+        let events = game.drain_events();
+        assert_eq!(events, vec![GameEvent::FoodExpired { kind: FoodKind::Golden, position: (0, 0) }]);
+        assert_eq!(game.foods.len(), 1, "Expired food should come back");
+        assert_ne!(game.foods[0].position, (0, 0));
+    }
+
+    #[test]
+    fn test_food_weights_pick_kinds() {
+        let mut options = default_game_options();
+        options.seed = Some(3);
+        options.food_count = 20;
+        options.grid_width = 20;
+        options.grid_height = 20;
+        options.food_weights = FoodWeights { regular: 0, golden: 1, ..FoodWeights::default() };
+
+        let mut game = Game::new(options.clone(), SeededRandomizer::new(0));
+        game.initialize().unwrap();
+        game.reset();
+        assert!(game.foods.iter().all(|food| food.kind == FoodKind::Golden && food.lifetime.is_some()));
+
+        options.food_weights = FoodWeights { regular: 1, bonus: 1, poison: 1, ..FoodWeights::default() };
+        game.apply_options_and_reset(options).unwrap();
+
+        for kind in [FoodKind::Regular, FoodKind::Bonus, FoodKind::Poison] {
+            assert!(game.foods.iter().any(|food| food.kind == kind), "{:?} should spawn", kind);
+        }
+        assert!(game.foods.iter().all(|food| food.kind != FoodKind::Golden), "Kinds without weight should not spawn");
+    }
+
+    #[test]
+    fn test_levels_up_as_the_score_grows() {
+        let mut options = default_game_options();
+        options.grid_width = 20;
+        options.grid_height = 20;
+        options.levels = vec![
+            Level { score: 1, speed: 1.5, obstacles: 2, food_count: Some(5) },
+            Level { score: 2, speed: 2.0, obstacles: 0, food_count: None },
+        ];
+        let mut game = Game::new(options, OsRandomizer::new());
+        game.initialize().unwrap();
+        game.reset();
+        game.drain_events();
+
+        place_food_ahead(&mut game, FoodKind::Regular);
+        game.update();
+
+        let events = game.drain_events();
+        assert!(events.contains(&GameEvent::LevelUp { level: 1 }));
+        assert_eq!(events.iter().filter(|event| matches!(event, GameEvent::ObstacleSpawned { .. })).count(), 2);
+        assert_eq!(game.level(), 1);
+        assert_eq!(game.speed_multiplier(), 1.5);
+        assert_eq!(game.foods.len(), 5);
+
+        let (x, y) = game.players[0].snake.get_head_position();
+        let near_head = |(ox, oy): (i32, i32)| (ox - x).abs() <= LEVEL_OBSTACLE_CLEARANCE && (oy - y).abs() <= LEVEL_OBSTACLE_CLEARANCE;
+        assert!(game.obstacles.iter().flat_map(Obstacle::cells).all(|cell| !near_head(cell)), "Obstacles should not drop in front of the snake");
+
+        place_food_ahead(&mut game, FoodKind::Regular);
+        game.update();
+        assert_eq!(game.level(), 2);
+        assert_eq!(game.speed_multiplier(), 2.0);
+        assert_eq!(game.foods.len(), 1, "The food count carries over when a level leaves it alone");
+        assert_eq!(game.snapshot().level, 2);
+
+        game.reset();
+        assert_eq!(game.level(), 0);
+        assert_eq!(game.speed_multiplier(), 1.0);
+        assert!(game.obstacles.is_empty());
     }
 
     #[test]
@@ -309,4 +1544,24 @@ mod tests {
 
         assert_eq!(game.foods.len() as u32, game.options.food_count, "Food count should reset");
     }
+
+    #[test]
+    fn test_reset_places_food_on_distinct_cells() {
+        let mut options = default_game_options();
+        options.grid_width = MIN_GRID_SIZE;
+        options.grid_height = MIN_GRID_SIZE;
+        options.food_count = GameOptions::max_food_count(options.grid());
+
+        for seed in 0..20 {
+            options.seed = Some(seed);
+            let mut game = Game::new(options.clone(), SeededRandomizer::new(0));
+            game.reset();
+
+            let mut positions: Vec<_> = game.foods.iter().map(|food| food.position).collect();
+            positions.sort();
+            positions.dedup();
+
+            assert_eq!(positions.len() as u32, options.food_count, "Seed {} stacked food on the same cell", seed);
+        }
+    }
 }
@@ -1,9 +1,9 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use log::debug;
+use log::{debug, warn};
 use web_sys::{Document, HtmlCanvasElement, Window};
 
-use crate::{abstractions::{frame_scheduler::{WasmClosureWrapper, WebFrameScheduler}, *}, game::Game, models::{GameOptions, GameResult, GameState, VerticePayload}, randomizer::{JsRandomizer, Randomizer}, utils::create_key_direction_map};
+use crate::{abstractions::{frame_scheduler::{WasmClosureWrapper, WebFrameScheduler}, *}, ai::{create_ai_controller, StrategyAi}, error::GameError, event_bus::{EventBus, SubscriptionId}, events::GameEvent, game::Game, map::GameMap, net::{ClientMessage, GameClient, ServerMessage}, models::{CellInstance, Direction, GameOptions, GameResult, GameState, GameStats, InvalidTransition, KeyBindings, PlayerController}, randomizer::{Randomizer, SeededRandomizer}, replay::{Replay, ReplayPlayer}, save::SavedGame, timestep::{FixedTimestep, MAX_CATCH_UP_STEPS}, utils::create_key_direction_map, viewport::GridViewport};
 
 pub type WasmGameOrchestrator<T> = GameOrchestrator<
     HtmlCanvasElement,
@@ -11,10 +11,10 @@ pub type WasmGameOrchestrator<T> = GameOrchestrator<
     Window,
     WasmClosureWrapper,
     T,
-    JsRandomizer,
+    SeededRandomizer,
     WebGl2Renderer,
     WebFrameScheduler,
-    GreedyBfsAi>;
+    StrategyAi>;
 
 pub struct GameOrchestrator <C, D, W, CW, T, R, RE, FS, A>
 where
@@ -30,6 +30,7 @@ where
 {
     options: GameOptions,
     state: GameState,
+    resume_state: GameState,
     canvas_provider: C,
     document_provider: D,
     window_provider: W,
@@ -37,12 +38,23 @@ where
     game: Game<R>,
     renderer: RE,
     frame_scheduler: FS,
-    on_score: T,
-    on_game_over: T,
+    event_bus: EventBus<T>,
     callback: Option<CW>,
-    callback_handle: ClosureHandle,
-    last_timestamp: f64,
-    ai_controller: A
+    // The animation frame the loop is waiting for, so starting again does not start a second loop.
+    scheduled_frame: Option<ClosureHandle>,
+    timestep: FixedTimestep,
+    viewport: GridViewport,
+    // Refilled every frame, so drawing does not allocate once the board has been drawn at its largest.
+    instances: Vec<CellInstance>,
+    ai_controller: A,
+    player_ais: Vec<Option<Box<dyn AiController>>>,
+    client: Option<GameClient<Box<dyn Transport<ClientMessage, ServerMessage>>>>,
+    offline_options: Option<GameOptions>,
+    // What the user had set up before a replay took over the board, put back once they play again.
+    options_before_replay: Option<(GameOptions, Option<GameMap>)>,
+    // None for a loaded game, whose start was never recorded.
+    replay: Option<Replay>,
+    replay_player: Option<ReplayPlayer>
 }
 
 impl<C, D, W, CW, T, R, RE, FS, A> GameOrchestrator<C, D, W, CW, T, R, RE, FS, A>
@@ -56,6 +68,7 @@ where
     RE: Renderer + 'static,
     FS: FrameScheduler<CW> + 'static,
     A: AiController + 'static {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         options: GameOptions,
         canvas_provider: C,
@@ -65,14 +78,16 @@ where
         frame_scheduler: FS,
         renderer: RE,
         randomizer: R,
-        ai_controller: A,
-        on_score: T,
-        on_game_over: T) -> Self {
-        let game = Game::new(options.clone(), randomizer);
+        ai_controller: A) -> Self {
+        let game = Game::new(options, randomizer);
+        let options = game.options().clone();
+        let player_ais = Self::create_player_ais(&options);
+        let viewport = GridViewport::fit(0.0, 0.0, 1.0, options.grid());
 
         GameOrchestrator {
             options,
             state: GameState::Idle,
+            resume_state: GameState::Idle,
             game,
             canvas_provider,
             document_provider,
@@ -80,141 +95,477 @@ where
             closure_wrapper,
             frame_scheduler,
             renderer,
-            on_score,
-            on_game_over,
-            last_timestamp: 0.0,
+            event_bus: EventBus::new(),
             callback: None,
-            callback_handle: 0,
-            ai_controller
+            scheduled_frame: None,
+            timestep: FixedTimestep::new(),
+            viewport,
+            instances: vec![],
+            ai_controller,
+            player_ais,
+            client: None,
+            offline_options: None,
+            options_before_replay: None,
+            replay: None,
+            replay_player: None
         }
     }
 
-    pub fn initialize(&mut self) {
-        self.renderer.setup();
-        self.game.initialize();
+    // Players set up as AI get their own controller, the rest are steered by keys or the demo AI.
+    fn create_player_ais(options: &GameOptions) -> Vec<Option<Box<dyn AiController>>> {
+        options.player_options().iter().map(|player| match player.controller {
+            PlayerController::Ai { strategy } => Some(create_ai_controller(strategy)),
+            PlayerController::Human { .. } => None,
+        }).collect()
+    }
+
+    pub fn initialize(&mut self) -> Result<(), GameError> {
+        self.renderer.setup()?;
+        self.game.initialize()
     }
 
+    // Fills the window with a canvas as sharp as the screen, with the grid fitted inside it.
     pub fn resize(&mut self) {
-        let width = self.window_provider.get_inner_width() as u32;
-        let height = self.window_provider.get_inner_height() as u32;
-        
-        self.canvas_provider.set_size(width, height);
-        self.renderer.set_viewport(width as i32, height as i32);
+        let width = self.window_provider.get_inner_width();
+        let height = self.window_provider.get_inner_height();
+        let pixel_ratio = self.window_provider.get_device_pixel_ratio();
+
+        self.viewport = GridViewport::fit(width, height, pixel_ratio, self.options.grid());
+        let (device_width, device_height) = self.viewport.device_size();
+
+        self.canvas_provider.set_size(device_width, device_height);
+        self.canvas_provider.set_display_size(width, height);
+        self.apply_viewport();
     }
 
-    pub fn play(&mut self) {
-        self.game.reset();
+    fn apply_viewport(&mut self) {
+        let (x, y, width, height) = self.viewport.device_rect();
+        self.renderer.set_viewport(x, y, width, height);
+        self.renderer.set_grid(self.viewport.grid());
+    }
+
+    // Where the grid is on the canvas, so overlays can line up with the cells.
+    pub fn viewport(&self) -> &GridViewport {
+        &self.viewport
     }
 
-    pub fn stop(&mut self) {
-        self.state = GameState::Paused;
+    pub fn state(&self) -> GameState {
+        self.state
     }
 
-    pub fn reset(&mut self, state: GameState) {
-        self.state = state;
+    fn transition_to(&mut self, state: GameState) -> Result<(), InvalidTransition> {
+        self.state = self.state.transition_to(state)?;
+
+        if state.is_running() {
+            self.game.set_ai_playing(state == GameState::AiPlaying);
+        }
+
+        Ok(())
+    }
+
+    // Freezes the game where it is. The pending frame is cancelled so nothing runs until `resume`.
+    pub fn pause(&mut self) -> Result<(), GameError> {
+        let state = self.state;
+        self.transition_to(GameState::Paused)?;
+        self.resume_state = state;
+
+        if let Some(handle) = self.scheduled_frame.take() {
+            self.frame_scheduler.cancel(handle);
+        }
+
+        self.event_bus.emit(GameEvent::Paused);
+        Ok(())
+    }
+
+    // Carries on with the paused game. The clock restarts too, so the time spent paused
+    // does not count towards the next tick.
+    pub fn resume(&mut self) -> Result<(), GameError> {
+        if self.state != GameState::Paused {
+            return Err(InvalidTransition { from: self.state, to: self.resume_state }.into());
+        }
+
+        self.transition_to(self.resume_state)?;
+        self.timestep.resume();
+        self.schedule_frame();
+        self.event_bus.emit(GameEvent::Resumed);
+
+        Ok(())
+    }
+
+    // Starts a fresh game, demo or replay in `state`, whatever was running before.
+    pub fn restart(game_orchestrator: Rc<RefCell<Self>>, state: GameState) -> Result<(), GameError> {
+        {
+            let mut orchestrator = game_orchestrator.borrow_mut();
+            orchestrator.state.transition_to(state)?;
+
+            if state != GameState::Replaying {
+                orchestrator.finish_replay()?;
+                orchestrator.reset();
+            }
+        }
+
+        Self::start_game_loop(game_orchestrator, state)
+    }
+
+    fn reset(&mut self) {
         self.game.reset();
+        self.start_recording();
+        self.dispatch_game_events();
     }
 
-    pub fn start_game_loop(game_orchestrator: Rc<RefCell<Self>>, is_ai_playing: bool) {
+    pub fn subscribe(&mut self, event_name: &str, callback: T) -> SubscriptionId {
+        self.event_bus.subscribe(event_name, callback)
+    }
 
-        let callback: Box<dyn FnMut(f64) + 'static> = {
-            let game_orchestrator = game_orchestrator.clone();
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.event_bus.unsubscribe(id)
+    }
 
-            Box::new(move |timestamp: f64| {
+    fn dispatch_game_events(&mut self) {
+        for event in self.game.drain_events() {
+            self.event_bus.emit(event);
+        }
+    }
 
-                let mut orchestrator = game_orchestrator.borrow_mut();
+    // Subscribers may call any export, so they only hear about the events once nothing borrows the orchestrator.
+    pub fn send_events(game_orchestrator: &Rc<RefCell<Self>>) -> Result<(), GameError> {
+        let deliveries = game_orchestrator.borrow_mut().event_bus.take_deliveries();
+        deliveries.send()
+    }
 
-                if orchestrator.state == GameState::GameOver
-                    || orchestrator.state == GameState::Paused {
-                    debug!("game over exit loop");
-                    return;
-                }
+    // Frames and key presses have nobody to report to, so a failing subscriber is only logged.
+    fn send_events_or_warn(game_orchestrator: &Rc<RefCell<Self>>) {
+        if let Err(error) = Self::send_events(game_orchestrator) {
+            warn!("Could not send events: {}", error);
+        }
+    }
 
-                let diff = timestamp - orchestrator.last_timestamp;
+    pub fn get_stats(&self) -> &GameStats {
+        self.game.stats()
+    }
 
-                if diff < orchestrator.options.frame_threshold_ms {
-                    let callback = orchestrator.callback.as_ref().unwrap();
-                    orchestrator.frame_scheduler.request_frame_after(callback, diff as i32);
-                    return;
-                }
+    pub fn get_replay(&self) -> Result<&Replay, GameError> {
+        self.replay.as_ref().ok_or(GameError::NoReplay)
+    }
+
+    pub fn play_replay(&mut self, replay: Replay) -> Result<(), GameError> {
+        if !replay.is_supported() {
+            return Err(GameError::UnsupportedReplay(replay.version));
+        }
+
+        // Replaying several runs in a row still goes back to the user's own setup afterwards.
+        let options_before_replay = self.options_before_replay.take()
+            .unwrap_or_else(|| (self.game.base_options().clone(), self.game.map().cloned()));
+
+        let result = self.set_board(replay.playback_options(), replay.map.clone());
+        self.options_before_replay = Some(options_before_replay);
+        result?;
+
+        self.replay_player = Some(ReplayPlayer::new(&replay));
+        self.replay = Some(replay);
+
+        Ok(())
+    }
+
+    // The replay's options carry its seed, so keeping them would play the same board forever.
+    fn finish_replay(&mut self) -> Result<(), GameError> {
+        match self.options_before_replay.take() {
+            Some((options, map)) => self.set_board(options, map),
+            None => Ok(()),
+        }
+    }
+
+    fn set_board(&mut self, options: GameOptions, map: Option<GameMap>) -> Result<(), GameError> {
+        match map {
+            Some(map) => self.game.load_map(map)?,
+            None if self.game.map().is_some() => self.game.unload_map()?,
+            None => {}
+        }
+
+        self.apply_options_and_reset(options)
+    }
+
+    // A paused game is saved with whoever paused it. Nobody can take over a replay's inputs,
+    // so the user plays on from its board.
+    pub fn save_game(&self) -> SavedGame {
+        let state = if self.state == GameState::Paused { self.resume_state } else { self.state };
+
+        SavedGame {
+            state: if state == GameState::AiPlaying { state } else { GameState::UserPlaying },
+            ..self.game.save()
+        }
+    }
+
+    // Puts the saved board in place. The game only moves once the loop is started in `saved.state`.
+    pub fn load_game(&mut self, saved: &SavedGame) -> Result<(), GameError> {
+        if !saved.can_play_on() {
+            return Err(GameError::InvalidSave(format!("cannot play on as {:?}", saved.state)));
+        }
+
+        self.game.load(saved)?;
+        self.options_before_replay = None;
+        self.options = self.game.options().clone();
+        self.ai_controller.set_strategy(self.options.ai_strategy);
+        self.player_ais = Self::create_player_ais(&self.options);
+        self.replay_player = None;
+        self.replay = None;
+
+        Ok(())
+    }
+
+    // Hands the game over to a server: its snapshots replace the local state, which keeps
+    // being simulated in between so the snake answers keys without waiting for the round trip.
+    pub fn connect(&mut self, transport: Box<dyn Transport<ClientMessage, ServerMessage>>) {
+        self.replay_player = None;
+        let options_before_replay = self.options_before_replay.take().map(|(options, _)| options);
+        self.offline_options.get_or_insert_with(|| options_before_replay.unwrap_or_else(|| self.options.clone()));
+        self.client = Some(GameClient::new(transport));
+    }
+
+    // Goes back to a local game with the options from before connecting.
+    pub fn disconnect(&mut self) -> Result<(), GameError> {
+        self.client = None;
+
+        match self.offline_options.take() {
+            Some(options) => self.apply_options_and_reset(options),
+            None => Ok(()),
+        }
+    }
+
+    fn start_recording(&mut self) {
+        self.replay_player = None;
+        self.replay = Some(Replay::new(self.game.seed(), self.options.clone(), self.game.map().cloned()));
+    }
+
+    fn change_direction(&mut self, player: usize, direction: Direction) {
+        if let Some(client) = self.client.as_mut() {
+            // Only the player the server seated us at can be steered from here.
+            if client.player() != Some(player) {
+                return;
+            }
+
+            if let Err(error) = client.send_input(self.game.tick(), direction) {
+                warn!("Disconnecting from the server: {}", error);
+                self.disconnect().ok();
+                return;
+            }
+        }
+
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record(self.game.tick(), player, direction);
+        }
 
-                orchestrator.last_timestamp = timestamp;
+        if self.game.change_player_direction(player, direction) {
+            self.event_bus.emit(GameEvent::DirectionChanged { player, direction });
+        }
+    }
 
-                orchestrator.on_game_loop();
-                
-                let callback = orchestrator.callback.as_ref().unwrap();
-                orchestrator.frame_scheduler.request_frame(callback);
+    // AI players always steer themselves, human players only while the demo is running.
+    // A replay already holds every turn and a server steers its own AI players,
+    // so nobody is steered here in either case.
+    fn ai_directions(&self) -> Vec<(usize, Direction)> {
+        if self.replay_player.is_some() || self.client.is_some() {
+            return vec![];
+        }
 
+        self.game.players.iter().enumerate()
+            .filter(|(_, player)| player.is_alive())
+            .filter_map(|(index, player)| {
+                let controller: &dyn AiController = match self.player_ais.get(index).and_then(Option::as_ref) {
+                    Some(controller) => controller.as_ref(),
+                    None if self.state == GameState::AiPlaying => &self.ai_controller,
+                    None => return None,
+                };
+
+                controller.get_direction(
+                    &player.snake,
+                    &self.game.foods,
+                    &self.game.obstacles_for(index),
+                    self.options.grid(),
+                    self.options.boundary_mode)
+                    .map(|direction| (index, direction))
+            })
+            .collect()
+    }
+
+    // Requests the next frame unless one is already on its way.
+    fn schedule_frame(&mut self) {
+        if self.scheduled_frame.is_some() {
+            return;
+        }
+
+        if let Some(callback) = self.callback.as_ref() {
+            self.scheduled_frame = Some(self.frame_scheduler.request_frame(callback));
+        }
+    }
+
+    // Time between ticks. Levels and speed food change it while the game runs.
+    fn tick_ms(&self) -> f64 {
+        1000.0 / (self.options.fps as f64 * self.game.speed_multiplier())
+    }
+
+    pub fn start_game_loop(game_orchestrator: Rc<RefCell<Self>>, state: GameState) -> Result<(), GameError> {
+
+        let callback: Box<dyn FnMut(f64) + 'static> = {
+            let game_orchestrator = game_orchestrator.clone();
+
+            Box::new(move |timestamp: f64| {
+                game_orchestrator.borrow_mut().on_frame(timestamp);
+                Self::send_events_or_warn(&game_orchestrator);
             })
         };
 
         let mut orchestrator = game_orchestrator.borrow_mut();
-        let mut closure_wrapper = orchestrator.closure_wrapper.clone();
-        closure_wrapper.create(callback);
-        orchestrator.callback = Some(closure_wrapper.clone());
-        let callback_handle = orchestrator.frame_scheduler.request_frame(&closure_wrapper);
-        orchestrator.callback_handle = callback_handle;
+        orchestrator.transition_to(state)?;
+        orchestrator.timestep.reset();
+
+        // The loop keeps its closure, so starting again only has to make sure a frame is coming.
+        if orchestrator.callback.is_none() {
+            let mut closure_wrapper = orchestrator.closure_wrapper.clone();
+            closure_wrapper.create(callback);
+            orchestrator.callback = Some(closure_wrapper);
+        }
+
+        orchestrator.schedule_frame();
+        drop(orchestrator);
 
-        if is_ai_playing {
-            orchestrator.state = GameState::AiPlaying;
+        Self::send_events(&game_orchestrator)
+    }
+
+    fn on_frame(&mut self, timestamp: f64) {
+        self.scheduled_frame = None;
+
+        if !self.state.is_running() {
+            debug!("game over exit loop");
+            return;
         }
-        else {
-            orchestrator.state = GameState::UserPlaying;
+
+        // Every frame is drawn, the game itself only moves as many ticks as the time allows.
+        let tick_ms = self.tick_ms();
+        self.timestep.advance(timestamp, tick_ms, MAX_CATCH_UP_STEPS);
+
+        while self.state.is_running() {
+            let tick_ms = self.tick_ms();
+
+            if !self.timestep.step(tick_ms) {
+                break;
+            }
+
+            self.on_game_loop();
         }
 
+        // A finished game is drawn where it ended rather than halfway into the crash.
+        let alpha = if self.state.is_running() { self.timestep.alpha(self.tick_ms()) } else { 1.0 };
+        self.render(alpha as f32);
+        self.schedule_frame();
     }
 
     fn on_game_loop(&mut self) {
 
-        if let GameState::AiPlaying = self.state {
-            let ai_direction = self.ai_controller.get_direction(
-                &self.game.snake,
-                &self.game.foods,
-                &self.game.obstacles,
-                self.options.grid_size);
-
-            if let Some(direction) = ai_direction {
-                self.game.change_direction(direction);
+        let server_events = match self.client.as_mut().map(|client| client.sync(&mut self.game)).transpose() {
+            Ok(events) => events,
+            Err(error) => {
+                // Nothing the server says can be trusted after that, so carry on offline.
+                warn!("Disconnecting from the server: {}", error);
+                self.disconnect().ok();
+                None
             }
+        };
+
+        if self.game.options() != &self.options {
+            self.options = self.game.options().clone();
+        }
+
+        for (player, direction) in self.ai_directions() {
+            self.change_direction(player, direction);
+        }
+
+        if let Some(replay_player) = self.replay_player.as_mut() {
+            replay_player.apply(&mut self.game);
         }
 
         let game_result = self.game.update();
-    
-        let vertices = self.game.get_vertices();
-        let length = vertices.len();
-        let payload = VerticePayload {
-            data: vertices,
-            length: length,
-            vertice_size: length as i32 / 6
-        };
 
-        self.renderer.draw(&payload);
+        if let Some(events) = server_events {
+            // Only the server knows what really happened, local events were just predictions.
+            self.game.drain_events();
 
-        match game_result {
-            GameResult::Noop => {
+            let is_over = events.iter().any(|event| matches!(event, GameEvent::GameOver { .. } | GameEvent::MatchOver { .. }));
 
-            },
-            GameResult::Score => {
-                if let GameState::UserPlaying = self.state {
-                    self.on_score.invoke();
-                }
-            },
-            GameResult::Over => {
-                if let GameState::AiPlaying = self.state {
-                    self.reset(self.state);
-                }
-                else {
-                    self.on_game_over.invoke();
-                    self.state = GameState::GameOver;
-                }
-            },
+            for event in events {
+                self.event_bus.emit(event);
+            }
+
+            if is_over {
+                self.end_game();
+            }
+
+            return;
+        }
+
+        self.dispatch_game_events();
+
+        if game_result == GameResult::Over {
+            if let GameState::AiPlaying = self.state {
+                self.reset();
+            }
+            else {
+                self.end_game();
+            }
         }
 
     }
 
-    pub fn apply_options_and_reset(&mut self, options: GameOptions) {
-        self.options = options;
-        self.game.apply_options_and_reset(self.options.clone());
+    // Goes through the same checks as every other change of state, the demo for one never ends.
+    fn end_game(&mut self) {
+        if let Err(error) = self.transition_to(GameState::GameOver) {
+            warn!("Not ending the game: {}", error);
+        }
+    }
+
+    fn render(&mut self, alpha: f32) {
+        // Options, maps, replays and servers can all change the grid size under a running game.
+        if self.viewport.grid() != self.options.grid() {
+            let viewport = self.viewport;
+            self.viewport = GridViewport::fit(viewport.width, viewport.height, viewport.pixel_ratio, self.options.grid());
+            self.apply_viewport();
+        }
+
+        self.instances.clear();
+        self.game.collect_instances(alpha, &mut self.instances);
+        self.renderer.draw(&self.instances);
+    }
+
+    pub fn apply_options_and_reset(&mut self, options: GameOptions) -> Result<(), GameError> {
+        self.game.apply_options_and_reset(options)?;
+        self.options_before_replay = None;
+        self.options = self.game.options().clone();
+        self.ai_controller.set_strategy(self.options.ai_strategy);
+        self.player_ais = Self::create_player_ais(&self.options);
+        self.start_recording();
+        self.dispatch_game_events();
+
+        Ok(())
+    }
+
+    pub fn load_map(&mut self, map: GameMap) -> Result<(), GameError> {
+        self.finish_replay()?;
+        self.game.load_map(map)?;
+        self.options = self.game.options().clone();
+        self.start_recording();
+        self.dispatch_game_events();
+
+        Ok(())
+    }
+
+    pub fn unload_map(&mut self) -> Result<(), GameError> {
+        self.finish_replay()?;
+        self.game.unload_map()?;
+        self.options = self.game.options().clone();
+        self.start_recording();
+        self.dispatch_game_events();
+
+        Ok(())
     }
 
     pub fn setup_on_resize(game_orchestrator: Rc<RefCell<Self>>) {
@@ -234,20 +585,36 @@ where
     
     pub fn setup_key_bindings(game_orchestrator: Rc<RefCell<Self>>) {
 
-        let key_direction_map = create_key_direction_map();
+        let key_direction_maps: HashMap<KeyBindings, HashMap<String, Direction>> = [KeyBindings::All, KeyBindings::Wasd, KeyBindings::Arrows]
+            .into_iter()
+            .map(|bindings| (bindings, create_key_direction_map(bindings)))
+            .collect();
     
         let handler: Box<dyn FnMut(String) + 'static> = {
             let game_orchestrator = game_orchestrator.clone();
             Box::new(move |key: String| {
-                let mut game_orchestrator = game_orchestrator.borrow_mut();
-
-                if game_orchestrator.state != GameState::UserPlaying {
-                    return;
-                }
-        
-                if let Some(direction) = key_direction_map.get(&key) {
-                    game_orchestrator.game.change_direction(*direction);
+                {
+                    let mut game_orchestrator = game_orchestrator.borrow_mut();
+
+                    if game_orchestrator.state != GameState::UserPlaying {
+                        return;
+                    }
+
+                    // Each human player listens to their own keys, so one press can only turn the snakes bound to it.
+                    let turns: Vec<_> = game_orchestrator.game.players.iter().enumerate()
+                        .filter(|(_, player)| player.is_alive())
+                        .filter_map(|(index, player)| {
+                            let key_direction_map = key_direction_maps.get(&player.controller.key_bindings()?)?;
+                            key_direction_map.get(&key).map(|&direction| (index, direction))
+                        })
+                        .collect();
+
+                    for (player, direction) in turns {
+                        game_orchestrator.change_direction(player, direction);
+                    }
                 }
+
+                Self::send_events_or_warn(&game_orchestrator);
             })
         };
     
@@ -261,22 +628,26 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
 
     use crate::abstractions::canvas_provider::MockCanvasProvider;
     use crate::game_orchestrator::GameOrchestrator;
-    use crate::models::{Difficulty, Direction, GameOptions, GameState};
-    use crate::randomizer::MockRandomizer;
+    use crate::error::GameError;
+    use crate::models::{AiStrategy, BoundaryMode, Direction, GameOptions, GameState, GridSize, InvalidTransition, KeyBindings, PlayerController, PlayerOptions};
+    use crate::net::{loopback, GameServer};
+    use crate::randomizer::{MockRandomizer, SeededRandomizer};
+    use crate::replay::{Replay, ReplayInput};
+    use crate::save::SavedGame;
     use crate::abstractions::frame_scheduler::MockFrameScheduler;
     use crate::abstractions::renderer::MockRenderer;
     use crate::game_orchestrator::document_provider::MockDocumentProvider;
     use crate::game_orchestrator::window_provider::MockWindowProvider;
     use crate::game_orchestrator::frame_scheduler::MockClosureWrapper;
-    use crate::game_orchestrator::ai_controller::MockAiController;
+    use crate::abstractions::ai_controller::MockAiController;
     use crate::abstractions::invoke_js::MockInvokeJsStub;
     
-    use mockall::{mock, predicate::*};
+    use mockall::predicate::*;
 
     type TestGameOrchestrator = GameOrchestrator<
         MockCanvasProvider,
@@ -314,10 +685,8 @@ mod tests {
         let renderer = MockRenderer::new();
         let randomizer = MockRandomizer::new();
         let ai_controller = MockAiController::new();
-        let on_score = MockInvokeJsStub::new();
-        let on_game_over = MockInvokeJsStub::new();
         
-        let orchestrator = GameOrchestrator::new(
+        let orchestrator: TestGameOrchestrator = GameOrchestrator::new(
             options,
             canvas,
             document,
@@ -327,8 +696,6 @@ mod tests {
             renderer,
             randomizer,
             ai_controller,
-            on_score,
-            on_game_over,
         );
         
         assert_eq!(orchestrator.state, GameState::Idle);
@@ -341,30 +708,233 @@ mod tests {
         dependencies
             .mock_renderer
             .expect_setup()
-            .return_const(());
+            .returning(|| Ok(()));
 
         dependencies.mock_ai_controller
             .expect_get_direction()
-            .with(always(), always(), always(), always())
-            .returning(|_, _, _, _| Some(Direction::Up));
+            .with(always(), always(), always(), always(), always())
+            .returning(|_, _, _, _, _| Some(Direction::Up));
 
         let mut orchestrator = setup_orchestrator(dependencies);
-        orchestrator.initialize();
+        orchestrator.initialize().unwrap();
         orchestrator.state = GameState::AiPlaying;
 
         orchestrator.on_game_loop();
         
-        assert_ne!(orchestrator.game.direction, Direction::Right);
+        assert_ne!(orchestrator.game.players[0].direction, Direction::Right);
+    }
+
+    #[test]
+    fn should_switch_ai_strategy_on_apply_options() {
+        let mut dependencies = setup_dependencies();
+
+        dependencies.mock_ai_controller
+            .expect_set_strategy()
+            .with(eq(AiStrategy::AStar))
+            .times(1)
+            .return_const(());
+
+        let mut orchestrator = setup_orchestrator(dependencies);
+
+        let mut options = orchestrator.options.clone();
+        options.ai_strategy = AiStrategy::AStar;
+        orchestrator.apply_options_and_reset(options).unwrap();
+    }
+
+    #[test]
+    fn should_steer_ai_players_while_user_plays() {
+        let mut dependencies = setup_dependencies();
+
+        dependencies.mock_ai_controller
+            .expect_set_strategy()
+            .return_const(());
+
+        let mut orchestrator = setup_orchestrator(dependencies);
+
+        let mut options = orchestrator.options.clone();
+        options.players = vec![
+            PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys: KeyBindings::Wasd } },
+            PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Ai { strategy: AiStrategy::Greedy } },
+        ];
+        orchestrator.apply_options_and_reset(options).unwrap();
+        orchestrator.state = GameState::UserPlaying;
+
+        orchestrator.on_game_loop();
+
+        let inputs = &orchestrator.get_replay().unwrap().inputs;
+        assert!(inputs.iter().all(|&ReplayInput(_, _, player)| player == 1), "Only the AI player should have turned");
+        assert!(!inputs.is_empty(), "The AI player should head for the food");
+    }
+
+    #[test]
+    fn should_follow_server_in_client_mode() {
+        let mut dependencies = setup_dependencies();
+
+        dependencies.mock_ai_controller
+            .expect_set_strategy()
+            .return_const(());
+
+        let mut orchestrator = setup_orchestrator(dependencies);
+
+        let mut options = orchestrator.options.clone();
+        options.seed = Some(3);
+        options.boundary_mode = BoundaryMode::Solid;
+        options.players = vec![
+            PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys: KeyBindings::Wasd } },
+            PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Human { keys: KeyBindings::Arrows } },
+        ];
+        let mut server: GameServer<SeededRandomizer, _> = GameServer::new(options, None, SeededRandomizer::new(3)).unwrap();
+        let (transport, connection) = loopback();
+        server.connect(connection);
+
+        orchestrator.connect(Box::new(transport));
+        orchestrator.state = GameState::UserPlaying;
+        orchestrator.on_game_loop();
+
+        assert_eq!(orchestrator.game.players.len(), 2, "The server's options should replace the local ones");
+
+        orchestrator.change_direction(1, Direction::Up);
+        orchestrator.change_direction(0, Direction::Down);
+        server.tick();
+        assert_eq!(server.game().players[0].direction, Direction::Right, "The turn was made a tick ahead of the server");
+        server.tick();
+        orchestrator.on_game_loop();
+
+        assert_eq!(server.game().players[0].direction, Direction::Down);
+        assert_eq!(server.game().players[1].direction, Direction::Right, "Other players are steered by their own clients");
+        let (x, y) = server.game().players[0].snake.get_head_position();
+        assert_eq!(orchestrator.game.players[0].snake.get_head_position(), (x, y - 1), "The client should predict one tick ahead");
+
+        orchestrator.disconnect().unwrap();
+        assert_eq!(orchestrator.game.players.len(), 1, "Disconnecting should restore the local options");
+    }
+
+    #[test]
+    fn should_pause_and_resume_where_it_left_off() {
+        let mut dependencies = setup_dependencies();
+        expect_closure(&mut dependencies);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_request_frame()
+            .times(2)
+            .return_const(7);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_cancel()
+            .with(eq(7))
+            .times(1)
+            .return_const(());
+
+        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
+        GameOrchestrator::restart(orchestrator.clone(), GameState::UserPlaying).unwrap();
+
+        let mut orchestrator = orchestrator.borrow_mut();
+        orchestrator.on_game_loop();
+        let head = orchestrator.game.players[0].snake.get_head_position();
+
+        orchestrator.pause().unwrap();
+        assert_eq!(orchestrator.state(), GameState::Paused);
+
+        orchestrator.resume().unwrap();
+        assert_eq!(orchestrator.state(), GameState::UserPlaying);
+        assert_eq!(orchestrator.game.players[0].snake.get_head_position(), head, "Resuming should not reset the game");
+        assert_eq!(orchestrator.game.tick(), 1);
     }
 
     #[test]
-    fn should_reset_state() {
+    fn should_load_games_with_whoever_was_playing() {
+        let mut dependencies = setup_dependencies();
+
+        dependencies.mock_ai_controller
+            .expect_set_strategy()
+            .return_const(());
+
+        let mut orchestrator = setup_orchestrator(dependencies);
+        orchestrator.state = GameState::AiPlaying;
+        orchestrator.pause().unwrap();
+
+        let saved = orchestrator.save_game();
+        assert_eq!(saved.state, GameState::AiPlaying, "A paused game should be saved with whoever paused it");
+
+        orchestrator.load_game(&saved).unwrap();
+        assert_eq!(orchestrator.get_replay().unwrap_err(), GameError::NoReplay, "A loaded game did not start from a fresh board");
+
+        let paused = SavedGame { state: GameState::Paused, ..saved.clone() };
+        assert!(matches!(orchestrator.load_game(&paused), Err(GameError::InvalidSave(_))));
+
+        let mut json = serde_json::to_value(&saved).unwrap();
+        json.as_object_mut().unwrap().remove("state");
+        let older: SavedGame = serde_json::from_value(json).unwrap();
+        assert_eq!(older.state, GameState::UserPlaying, "Older saves should go back to the user");
+    }
+
+    #[test]
+    fn should_reject_invalid_transitions() {
         let dependencies = setup_dependencies();
 
         let mut orchestrator = setup_orchestrator(dependencies);
-        orchestrator.play();
-        orchestrator.stop();
-        orchestrator.reset(GameState::GameOver);
+
+        let error = InvalidTransition { from: GameState::Idle, to: GameState::Paused };
+        assert_eq!(orchestrator.pause(), Err(GameError::InvalidTransition(error)));
+        assert!(orchestrator.resume().is_err(), "Only a paused game can be resumed");
+        assert_eq!(orchestrator.state(), GameState::Idle);
+
+        orchestrator.state = GameState::GameOver;
+        assert!(orchestrator.pause().is_err(), "A finished game cannot be paused");
+    }
+
+    #[test]
+    fn should_restart_after_game_over() {
+        let mut dependencies = setup_dependencies();
+        expect_closure(&mut dependencies);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_request_frame()
+            .times(1)
+            .return_const(0);
+
+        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
+        orchestrator.borrow_mut().state = GameState::GameOver;
+        orchestrator.borrow_mut().game.players[0].kill();
+
+        GameOrchestrator::restart(orchestrator.clone(), GameState::UserPlaying).unwrap();
+        // The first frame is still pending, so restarting again must not start a second loop.
+        GameOrchestrator::restart(orchestrator.clone(), GameState::AiPlaying).unwrap();
+
+        let orchestrator = orchestrator.borrow();
+        assert_eq!(orchestrator.state(), GameState::AiPlaying);
+        assert!(orchestrator.game.players[0].is_alive());
+    }
+
+    #[test]
+    fn should_restore_user_options_after_replay() {
+        let mut dependencies = setup_dependencies();
+        expect_closure(&mut dependencies);
+
+        dependencies.mock_ai_controller
+            .expect_set_strategy()
+            .return_const(());
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_request_frame()
+            .return_const(0);
+
+        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
+        let recorded_options = GameOptions { grid_width: 30, ..orchestrator.borrow().options.clone() };
+
+        orchestrator.borrow_mut().play_replay(Replay::new(42, recorded_options, None)).unwrap();
+        assert_eq!(orchestrator.borrow().options.seed, Some(42));
+        assert_eq!(orchestrator.borrow().options.grid_width, 30);
+
+        GameOrchestrator::restart(orchestrator.clone(), GameState::UserPlaying).unwrap();
+
+        let orchestrator = orchestrator.borrow();
+        assert_eq!(orchestrator.options.seed, None, "The replay's seed should not stick to the user's games");
+        assert_eq!(orchestrator.options.grid_width, 20);
     }
 
     #[test]
@@ -381,9 +951,23 @@ mod tests {
             .expect_get_inner_height()
             .return_const(480);
 
+        dependencies
+            .mock_window_rovider
+            .expect_get_device_pixel_ratio()
+            .return_const(2.0);
+
         dependencies
             .mock_canvas_provider
             .expect_set_size()
+            .with(eq(1200), eq(960))
+            .times(1)
+            .return_const(());
+
+        dependencies
+            .mock_canvas_provider
+            .expect_set_display_size()
+            .with(eq(600.0), eq(480.0))
+            .times(1)
             .return_const(());
 
         dependencies
@@ -394,10 +978,20 @@ mod tests {
         dependencies
             .mock_renderer
             .expect_set_viewport()
+            .with(eq(120), eq(0), eq(960), eq(960))
+            .times(1)
+            .return_const(());
+
+        dependencies
+            .mock_renderer
+            .expect_set_grid()
+            .with(eq(GridSize::square(20)))
+            .times(1)
             .return_const(());
 
         let mut orchestrator = setup_orchestrator(dependencies);
         orchestrator.resize();
+        assert_eq!(orchestrator.viewport().cell_size, 24.0, "A 20x20 grid in a 480 pixel high window");
     }
 
     #[test]
@@ -407,15 +1001,16 @@ mod tests {
         dependencies
             .mock_renderer
             .expect_setup()
-            .return_const(());
+            .returning(|| Ok(()));
 
         dependencies
             .mock_on_game_over
             .expect_invoke()
-            .returning(|| {});
+            .times(1)
+            .returning(|_| Ok(()));
 
         let mut orchestrator = setup_orchestrator(dependencies);
-        orchestrator.initialize();
+        orchestrator.initialize().unwrap();
 
         orchestrator.state = GameState::UserPlaying;
 
@@ -428,6 +1023,49 @@ mod tests {
         orchestrator.on_game_loop();
 
         assert_eq!(orchestrator.state, GameState::GameOver);
+        orchestrator.event_bus.take_deliveries().send().unwrap();
+    }
+
+    #[test]
+    fn subscribers_can_call_back_into_the_orchestrator() {
+        let mut dependencies = setup_dependencies();
+        expect_closure(&mut dependencies);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_request_frame()
+            .return_const(0);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_cancel()
+            .return_const(());
+
+        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
+        let seen_state = Rc::new(Cell::new(None));
+        let mut on_paused = MockInvokeJsStub::new();
+
+        {
+            let orchestrator = orchestrator.clone();
+            let seen_state = seen_state.clone();
+
+            on_paused
+                .expect_invoke()
+                .times(1)
+                .returning_st(move |_| {
+                    seen_state.set(Some(orchestrator.borrow_mut().state()));
+                    Ok(())
+                });
+        }
+
+        orchestrator.borrow_mut().subscribe("paused", on_paused);
+        GameOrchestrator::restart(orchestrator.clone(), GameState::UserPlaying).unwrap();
+
+        orchestrator.borrow_mut().pause().unwrap();
+        assert_eq!(seen_state.get(), None, "Nobody should be called while the orchestrator is borrowed");
+
+        GameOrchestrator::send_events(&orchestrator).unwrap();
+        assert_eq!(seen_state.get(), Some(GameState::Paused));
     }
 
     #[test]
@@ -445,7 +1083,29 @@ mod tests {
         // Simulate key press event and check if direction changes
         let mut orchestrator = orchestrator.borrow_mut();
         orchestrator.game.change_direction(Direction::Up);
-        assert_eq!(orchestrator.game.direction, Direction::Up);
+        assert_eq!(orchestrator.game.players[0].heading(), Direction::Up);
+    }
+
+    #[test]
+    fn should_record_direction_changes() {
+        let mut dependencies = setup_dependencies();
+
+        dependencies
+            .mock_renderer
+            .expect_setup()
+            .returning(|| Ok(()));
+
+        let mut orchestrator = setup_orchestrator(dependencies);
+        orchestrator.initialize().unwrap();
+        orchestrator.reset();
+        orchestrator.state = GameState::UserPlaying;
+
+        orchestrator.change_direction(0, Direction::Up);
+        orchestrator.on_game_loop();
+        orchestrator.change_direction(0, Direction::Left);
+
+        let inputs = &orchestrator.get_replay().unwrap().inputs;
+        assert_eq!(inputs, &vec![ReplayInput(0, Direction::Up, 0), ReplayInput(1, Direction::Left, 0)]);
     }
 
     #[test]
@@ -457,6 +1117,18 @@ mod tests {
             .expect_on_key_down()
             .returning(|_| {});
 
+        expect_closure(&mut dependencies);
+
+        dependencies
+            .mock_frame_scheduler
+            .expect_request_frame()
+            .return_const(0);
+
+        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
+        GameOrchestrator::start_game_loop(orchestrator.clone(), GameState::UserPlaying).unwrap();
+    }
+
+    fn expect_closure(dependencies: &mut Dependencies) {
         dependencies
             .mock_closure_wrapper
             .expect_clone()
@@ -465,7 +1137,7 @@ mod tests {
 
                 wrapper
                     .expect_clone()
-                    .returning(|| MockClosureWrapper::new());
+                    .returning(MockClosureWrapper::new);
 
                 wrapper
                     .expect_create()
@@ -473,14 +1145,6 @@ mod tests {
 
                 wrapper
             });
-
-        dependencies
-            .mock_frame_scheduler
-            .expect_request_frame()
-            .return_const(0);
-
-        let orchestrator = Rc::new(RefCell::new(setup_orchestrator(dependencies)));
-        GameOrchestrator::start_game_loop(orchestrator.clone(), false);
     }
 
     fn setup_dependencies() -> Dependencies {
@@ -507,6 +1171,16 @@ mod tests {
             .expect_get_random_position_on_grid()
             .returning(|_| (5, 5));
 
+        dependencies
+            .mock_randomizer
+            .expect_next_seed()
+            .return_const(0u64);
+
+        dependencies
+            .mock_randomizer
+            .expect_reseed()
+            .return_const(());
+
         dependencies.mock_renderer
             .expect_draw()
             .returning(|_| {});
@@ -517,16 +1191,11 @@ mod tests {
 
     fn setup_orchestrator(dependencies: Dependencies) -> TestGameOrchestrator {
         let game_options = GameOptions {
-            id: "".into(),
-            fps: 10,
-            frame_threshold_ms: 10.0,
-            grid_size: 20,
             food_count: 1,
-            difficulty: Difficulty::Easy,
-            snake_color: "#00FF00".to_string(),
+            ..GameOptions::test_default()
         };
 
-        let orchestrator = GameOrchestrator::new(
+        let mut orchestrator = GameOrchestrator::new(
             game_options,
             dependencies.mock_canvas_provider,
             dependencies.mock_document_provider,
@@ -536,10 +1205,11 @@ mod tests {
             dependencies.mock_renderer,
             dependencies.mock_randomizer,
             dependencies.mock_ai_controller,
-            dependencies.mock_on_score,
-            dependencies.mock_on_game_over,
         );
 
+        orchestrator.subscribe("foodEaten", dependencies.mock_on_score);
+        orchestrator.subscribe("gameOver", dependencies.mock_on_game_over);
+
         orchestrator
     }
     
@@ -1,21 +1,22 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use js_sys::Function;
 use wasm_bindgen::prelude::*;
-use web_sys::{window, Document, HtmlCanvasElement, WebGl2RenderingContext, Window};
+use web_sys::{window, WebGl2RenderingContext};
 
 use crate::abstractions::frame_scheduler::{WasmClosureWrapper, WebFrameScheduler};
-use crate::abstractions::{ClosureWrapper, GreedyBfsAi, InvokeJs, WebGl2Renderer};
+use crate::abstractions::{InvokeJs, WebGl2Renderer};
+use crate::ai::StrategyAi;
+use crate::error::GameError;
 use crate::game_orchestrator::{GameOrchestrator, WasmGameOrchestrator};
 use crate::models::GameOptions;
-use crate::randomizer::JsRandomizer;
+use crate::randomizer::SeededRandomizer;
 
 pub trait GameOrchestratorFactory<T: InvokeJs> {
     fn create(
         options: GameOptions,
         on_score: T,
         on_game_over: T
-    ) -> Rc<RefCell<WasmGameOrchestrator<T>>>;
+    ) -> Result<Rc<RefCell<WasmGameOrchestrator<T>>>, GameError>;
 }
 pub struct WasmGameOrchestratorFactory;
 
@@ -24,24 +25,27 @@ impl<T: InvokeJs> GameOrchestratorFactory<T> for WasmGameOrchestratorFactory {
         options: GameOptions,
         on_score: T,
         on_game_over: T
-    ) -> Rc<RefCell<WasmGameOrchestrator<T>>> {
+    ) -> Result<Rc<RefCell<WasmGameOrchestrator<T>>>, GameError> {
 
-    let window = window().unwrap();
-    let document = window.document().unwrap();
+    let window = window().ok_or(GameError::NoWindow)?;
+    let document = window.document().ok_or(GameError::NoWindow)?;
     let canvas = document
-        .get_element_by_id(&options.id).unwrap()
-        .dyn_into::<web_sys::HtmlCanvasElement>().unwrap();
+        .get_element_by_id(&options.id)
+        .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .ok_or_else(|| GameError::CanvasNotFound(options.id.clone()))?;
 
     let context = canvas
-        .get_context("webgl2").unwrap().unwrap()
-        .dyn_into::<WebGl2RenderingContext>().unwrap();
+        .get_context("webgl2").ok().flatten()
+        .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+        .ok_or(GameError::WebGl2Unavailable)?;
 
-    let randomizer = JsRandomizer;
+    let seed = options.seed.unwrap_or_else(|| (js_sys::Math::random() * u32::MAX as f64) as u64);
+    let randomizer = SeededRandomizer::new(seed);
     let frame_scheduler = WebFrameScheduler::new(window.clone());
     let renderer = WebGl2Renderer::new(context.clone());
-    let ai_controller = GreedyBfsAi::new();
+    let ai_controller = StrategyAi::new(options.ai_strategy);
     let closure_wrapper = WasmClosureWrapper::new();
-    let orchestrator=  GameOrchestrator::new(
+    let mut orchestrator=  GameOrchestrator::new(
         options,
         canvas,
         document,
@@ -50,10 +54,11 @@ impl<T: InvokeJs> GameOrchestratorFactory<T> for WasmGameOrchestratorFactory {
         frame_scheduler,
         renderer,
         randomizer,
-        ai_controller,
-        on_score,
-        on_game_over);
+        ai_controller);
 
-        Rc::new(RefCell::new(orchestrator))
+    orchestrator.subscribe("foodEaten", on_score);
+    orchestrator.subscribe("gameOver", on_game_over);
+
+        Ok(Rc::new(RefCell::new(orchestrator)))
     }
 }
@@ -0,0 +1,36 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+
+use crate::{abstractions::Transport, error::GameError, net::{ClientMessage, ServerMessage}, utils::to_js_value};
+
+pub type ServerInbox = Rc<RefCell<VecDeque<ServerMessage>>>;
+
+// Bridges a connection owned by JS, such as a WebSocket. Outgoing messages are handed to `send`
+// as plain objects and incoming ones wait in the inbox until the game loop picks them up.
+pub struct JsTransport {
+    send: Function,
+    inbox: ServerInbox,
+}
+
+impl JsTransport {
+    pub fn new(send: Function) -> (Self, ServerInbox) {
+        let inbox = ServerInbox::default();
+
+        (Self { send, inbox: inbox.clone() }, inbox)
+    }
+}
+
+impl Transport<ClientMessage, ServerMessage> for JsTransport {
+    fn send(&mut self, message: ClientMessage) -> Result<(), GameError> {
+        let payload = to_js_value(&message).map_err(|error| GameError::CallbackFailed(error.to_string()))?;
+        self.send.call1(&JsValue::null(), &payload).map_err(|error| GameError::CallbackFailed(format!("{:?}", error)))?;
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Option<ServerMessage> {
+        self.inbox.borrow_mut().pop_front()
+    }
+}
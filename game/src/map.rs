@@ -0,0 +1,379 @@
+use std::{collections::HashSet, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{BoundaryMode, Direction, GridSize, WithGridSize, MAX_GRID_SIZE, MIN_GRID_SIZE};
+
+pub const DEFAULT_SPAWN_LENGTH: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn contains(&self, position: (i32, i32)) -> bool {
+        position.0 >= self.x && position.0 < self.x + self.width
+            && position.1 >= self.y && position.1 < self.y + self.height
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (self.x..self.x + self.width).flat_map(move |x| (self.y..self.y + self.height).map(move |y| (x, y)))
+    }
+
+    fn is_inside_grid(&self, grid: GridSize) -> bool {
+        self.width > 0 && self.height > 0
+            && self.x >= 0 && self.y >= 0
+            && self.x + self.width <= grid.width && self.y + self.height <= grid.height
+    }
+}
+
+// A straight horizontal or vertical line of wall cells, both ends inclusive.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Wall {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+}
+
+impl Wall {
+    pub fn is_straight(&self) -> bool {
+        self.from.0 == self.to.0 || self.from.1 == self.to.1
+    }
+
+    pub fn as_rect(&self) -> Rect {
+        let x = self.from.0.min(self.to.0);
+        let y = self.from.1.min(self.to.1);
+
+        Rect {
+            x,
+            y,
+            width: self.from.0.max(self.to.0) - x + 1,
+            height: self.from.1.max(self.to.1) - y + 1,
+        }
+    }
+}
+
+// An obstacle of any shape: a single rectangle, several rectangles (L-shapes,
+// rooms) or an explicit list of cells.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(untagged, rename_all = "camelCase")]
+pub enum ObstacleShape {
+    Rect(Rect),
+    Rects { rects: Vec<Rect> },
+    Cells { cells: Vec<(i32, i32)> },
+}
+
+impl ObstacleShape {
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        match self {
+            ObstacleShape::Rect(rect) => rect.cells().collect(),
+            ObstacleShape::Rects { rects } => rects.iter().flat_map(Rect::cells).collect(),
+            ObstacleShape::Cells { cells } => cells.clone(),
+        }
+    }
+
+    fn is_inside_grid(&self, grid: GridSize) -> bool {
+        match self {
+            ObstacleShape::Rect(rect) => rect.is_inside_grid(grid),
+            ObstacleShape::Rects { rects } => !rects.is_empty() && rects.iter().all(|rect| rect.is_inside_grid(grid)),
+            ObstacleShape::Cells { cells } => !cells.is_empty() && cells.iter().all(|&cell| grid.contains(cell)),
+        }
+    }
+}
+
+fn default_spawn_length() -> usize {
+    DEFAULT_SPAWN_LENGTH
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Spawn {
+    pub position: (i32, i32),
+    pub direction: Direction,
+    #[serde(default = "default_spawn_length")]
+    pub length: usize,
+}
+
+impl Spawn {
+    pub fn body(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let (dx, dy) = self.direction.opposite().offset();
+        (0..self.length as i32).map(move |i| (self.position.0 + dx * i, self.position.1 + dy * i))
+    }
+}
+
+// Deserialized through `WithGridSize`, so maps from before the grid had two sides still load.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(remote = "Self", rename_all = "camelCase")]
+pub struct GameMap {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub grid_width: i32,
+    #[serde(default)]
+    pub grid_height: i32,
+    #[serde(default)]
+    pub boundary_mode: BoundaryMode,
+    #[serde(default)]
+    pub walls: Vec<Wall>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleShape>,
+    pub spawn: Spawn,
+    #[serde(default)]
+    pub food_zones: Vec<Rect>,
+}
+
+impl Serialize for GameMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameMap::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Map(#[serde(with = "GameMap")] GameMap);
+
+        let map = WithGridSize::<Map>::deserialize(deserializer)?;
+        Ok(map.into_value(|Map(map)| [&mut map.grid_width, &mut map.grid_height]).0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MapError {
+    InvalidGridSize(i32, i32),
+    WallNotStraight(usize),
+    WallOutsideGrid(usize),
+    ObstacleOutsideGrid(usize),
+    SpawnTooShort,
+    SpawnOutsideGrid,
+    SpawnBlocked((i32, i32)),
+    FoodZoneOutsideGrid(usize),
+    NoFreeFoodCell,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::InvalidGridSize(width, height) => write!(f, "gridWidth and gridHeight must be between {} and {}, got {}x{}", MIN_GRID_SIZE, MAX_GRID_SIZE, width, height),
+            MapError::WallNotStraight(index) => write!(f, "walls[{}] must be horizontal or vertical", index),
+            MapError::WallOutsideGrid(index) => write!(f, "walls[{}] extends outside the grid", index),
+            MapError::ObstacleOutsideGrid(index) => write!(f, "obstacles[{}] must have a positive size and fit inside the grid", index),
+            MapError::SpawnTooShort => write!(f, "spawn.length must be at least 2"),
+            MapError::SpawnOutsideGrid => write!(f, "spawn body extends outside the grid"),
+            MapError::SpawnBlocked(position) => write!(f, "spawn body overlaps a wall or obstacle at {:?}", position),
+            MapError::FoodZoneOutsideGrid(index) => write!(f, "foodZones[{}] must have a positive size and fit inside the grid", index),
+            MapError::NoFreeFoodCell => write!(f, "food zones have no cell free of walls, obstacles and the snake"),
+        }
+    }
+}
+
+impl GameMap {
+    pub fn grid(&self) -> GridSize {
+        GridSize::new(self.grid_width, self.grid_height)
+    }
+
+    // Walls and obstacles both end up as solid obstacles in the game, one cell set each.
+    pub fn solid_shapes(&self) -> Vec<Vec<(i32, i32)>> {
+        self.walls.iter().map(|wall| wall.as_rect().cells().collect())
+            .chain(self.obstacles.iter().map(ObstacleShape::cells))
+            .collect()
+    }
+
+    pub fn is_food_cell(&self, position: (i32, i32)) -> bool {
+        self.food_zones.is_empty() || self.food_zones.iter().any(|zone| zone.contains(position))
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<MapError>> {
+        let mut errors = vec![];
+
+        let grid = self.grid();
+
+        if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&grid.width) || !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&grid.height) {
+            return Err(vec![MapError::InvalidGridSize(grid.width, grid.height)]);
+        }
+
+        for (index, wall) in self.walls.iter().enumerate() {
+            if !wall.is_straight() {
+                errors.push(MapError::WallNotStraight(index));
+            }
+            else if !wall.as_rect().is_inside_grid(grid) {
+                errors.push(MapError::WallOutsideGrid(index));
+            }
+        }
+
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            if !obstacle.is_inside_grid(grid) {
+                errors.push(MapError::ObstacleOutsideGrid(index));
+            }
+        }
+
+        for (index, zone) in self.food_zones.iter().enumerate() {
+            if !zone.is_inside_grid(grid) {
+                errors.push(MapError::FoodZoneOutsideGrid(index));
+            }
+        }
+
+        let solid_cells: HashSet<(i32, i32)> = self.solid_shapes().into_iter().flatten().collect();
+        let is_solid = |position: (i32, i32)| solid_cells.contains(&position);
+
+        if self.spawn.length < 2 {
+            errors.push(MapError::SpawnTooShort);
+        }
+        else if !self.spawn.body().all(|segment| grid.contains(segment)) {
+            errors.push(MapError::SpawnOutsideGrid);
+        }
+        else if let Some(position) = self.spawn.body().find(|&position| is_solid(position)) {
+            errors.push(MapError::SpawnBlocked(position));
+        }
+
+        let has_free_food_cell = grid.cells()
+            .any(|position| self.is_food_cell(position)
+                && !is_solid(position)
+                && !self.spawn.body().any(|segment| segment == position));
+
+        if !has_free_food_cell {
+            errors.push(MapError::NoFreeFoodCell);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> GameMap {
+        GameMap {
+            name: "test".into(),
+            grid_width: 20,
+            grid_height: 20,
+            boundary_mode: BoundaryMode::Solid,
+            walls: vec![Wall { from: (0, 15), to: (9, 15) }],
+            obstacles: vec![ObstacleShape::Rect(Rect { x: 14, y: 2, width: 3, height: 1 })],
+            spawn: Spawn { position: (10, 5), direction: Direction::Up, length: 4 },
+            food_zones: vec![Rect { x: 0, y: 16, width: 20, height: 4 }],
+        }
+    }
+
+    #[test]
+    fn valid_map_passes() {
+        assert_eq!(map().validate(), Ok(()));
+    }
+
+    #[test]
+    fn wall_becomes_rect() {
+        let wall = Wall { from: (9, 15), to: (0, 15) };
+        assert_eq!(wall.as_rect(), Rect { x: 0, y: 15, width: 10, height: 1 });
+    }
+
+    #[test]
+    fn spawn_body_trails_behind_head() {
+        let body: Vec<_> = map().spawn.body().collect();
+        assert_eq!(body, vec![(10, 5), (10, 4), (10, 3), (10, 2)]);
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let mut map = map();
+        map.walls.push(Wall { from: (0, 0), to: (3, 3) });
+        map.obstacles.push(ObstacleShape::Rect(Rect { x: 18, y: 18, width: 5, height: 1 }));
+        map.spawn.position = (10, 1);
+
+        let errors = map.validate().unwrap_err();
+
+        assert_eq!(errors, vec![
+            MapError::WallNotStraight(1),
+            MapError::ObstacleOutsideGrid(1),
+            MapError::SpawnOutsideGrid,
+        ]);
+    }
+
+    #[test]
+    fn rejects_spawn_on_obstacle() {
+        let mut map = map();
+        map.spawn.position = (15, 5);
+        map.spawn.length = 4;
+
+        assert_eq!(map.validate(), Err(vec![MapError::SpawnBlocked((15, 2))]));
+    }
+
+    #[test]
+    fn rejects_fully_blocked_food_zone() {
+        let mut map = map();
+        map.food_zones = vec![Rect { x: 14, y: 2, width: 3, height: 1 }];
+
+        assert_eq!(map.validate(), Err(vec![MapError::NoFreeFoodCell]));
+    }
+
+    #[test]
+    fn parses_obstacle_shapes() {
+        let json = r#"[
+            { "x": 1, "y": 1, "width": 2, "height": 1 },
+            { "rects": [{ "x": 5, "y": 5, "width": 3, "height": 1 }, { "x": 5, "y": 6, "width": 1, "height": 2 }] },
+            { "cells": [[10, 10], [11, 11]] }
+        ]"#;
+
+        let shapes: Vec<ObstacleShape> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(shapes[0].cells(), vec![(1, 1), (2, 1)]);
+        assert_eq!(shapes[1].cells(), vec![(5, 5), (6, 5), (7, 5), (5, 6), (5, 7)]);
+        assert_eq!(shapes[2].cells(), vec![(10, 10), (11, 11)]);
+    }
+
+    #[test]
+    fn rejects_cells_outside_grid() {
+        let mut map = map();
+        map.obstacles.push(ObstacleShape::Cells { cells: vec![(3, 3), (20, 3)] });
+        map.obstacles.push(ObstacleShape::Rects { rects: vec![] });
+
+        assert_eq!(map.validate(), Err(vec![MapError::ObstacleOutsideGrid(1), MapError::ObstacleOutsideGrid(2)]));
+    }
+
+    #[test]
+    fn checks_each_side_of_a_wide_grid() {
+        let mut map = map();
+        map.grid_width = 30;
+        map.grid_height = 10;
+        map.walls.clear();
+        map.obstacles = vec![ObstacleShape::Cells { cells: vec![(28, 5)] }, ObstacleShape::Cells { cells: vec![(5, 15)] }];
+        map.food_zones = vec![Rect { x: 20, y: 0, width: 10, height: 10 }];
+        map.spawn.position = (25, 8);
+
+        assert_eq!(map.validate(), Err(vec![MapError::ObstacleOutsideGrid(1)]));
+    }
+
+    #[test]
+    fn rejects_invalid_grid_size() {
+        let mut map = map();
+        map.grid_height = 0;
+
+        assert_eq!(map.validate(), Err(vec![MapError::InvalidGridSize(20, 0)]));
+
+        map.grid_height = MAX_GRID_SIZE + 1;
+        assert_eq!(map.validate(), Err(vec![MapError::InvalidGridSize(20, MAX_GRID_SIZE + 1)]), "Maps should fit the same grid range as the options");
+    }
+
+    #[test]
+    fn reads_the_old_grid_size_as_both_sides() {
+        let mut json = serde_json::to_value(map()).unwrap();
+        assert_eq!(serde_json::from_value::<GameMap>(json.clone()).unwrap(), map(), "Maps should survive a round trip");
+
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("gridWidth");
+        fields.remove("gridHeight");
+        fields.insert("gridSize".into(), 25.into());
+
+        assert_eq!(serde_json::from_value::<GameMap>(json).unwrap().grid(), GridSize::square(25));
+    }
+}
@@ -1,30 +1,425 @@
+use std::{collections::BTreeMap, fmt};
+
+use csscolorparser::Color;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub const DEFAULT_INPUT_BUFFER: usize = 3;
+pub const MIN_GRID_SIZE: i32 = 8;
+pub const MAX_GRID_SIZE: i32 = 100;
+pub const MIN_FPS: i32 = 1;
+pub const MAX_FPS: i32 = 120;
+pub const MAX_INPUT_BUFFER: usize = 8;
+pub const MAX_PLAYERS: usize = 4;
+pub const MIN_LEVEL_SPEED: f64 = 0.25;
+pub const MAX_LEVEL_SPEED: f64 = 4.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum GameState {
     Idle = 0,
     AiPlaying = 1,
     UserPlaying = 2,
     Paused = 3,
     GameOver = 4,
+    Replaying = 5,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
+impl GameState {
+    pub fn is_running(&self) -> bool {
+        matches!(self, GameState::AiPlaying | GameState::UserPlaying | GameState::Replaying)
+    }
+
+    // A game, the demo or a replay can be (re)started from anywhere. Only a running game can be
+    // paused and only a game someone is watching can end, the demo starts over by itself.
+    pub fn can_transition_to(&self, next: GameState) -> bool {
+        match (self, next) {
+            (_, GameState::AiPlaying | GameState::UserPlaying | GameState::Replaying) => true,
+            (state, GameState::Paused) => state.is_running(),
+            (GameState::UserPlaying | GameState::Replaying, GameState::GameOver) => true,
+            _ => false,
+        }
+    }
+
+    pub fn transition_to(&self, next: GameState) -> Result<GameState, InvalidTransition> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        }
+        else {
+            Err(InvalidTransition { from: *self, to: next })
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: GameState,
+    pub to: GameState,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot go from {:?} to {:?}", self.from, self.to)
+    }
+}
+
+// Deserialized through `WithGridSize`, so options from before the grid had two sides still load.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(remote = "Self", rename_all = "camelCase")]
 pub struct GameOptions {
     pub id: String,
     pub snake_color: String,
     pub difficulty: Difficulty,
-    pub grid_size: i32,
+    #[serde(default)]
+    pub grid_width: i32,
+    #[serde(default)]
+    pub grid_height: i32,
     pub food_count: u32,
+    // Ticks per second. Drawing runs at the display's own rate.
     pub fps: i32,
-    pub frame_threshold_ms: f64
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub boundary_mode: BoundaryMode,
+    #[serde(default)]
+    pub ai_strategy: AiStrategy,
+    #[serde(default)]
+    pub players: Vec<PlayerOptions>,
+    // How many turns each player can queue ahead of the snake, at least one.
+    #[serde(default = "default_input_buffer")]
+    pub input_buffer: usize,
+    #[serde(default)]
+    pub food_weights: FoodWeights,
+    // The difficulty curve, ordered by score. Without levels the game stays as it started.
+    #[serde(default)]
+    pub levels: Vec<Level>
 }
 
-pub struct VerticePayload {
-    pub data: Vec<f32>,
-    pub length: usize,
-    pub vertice_size: i32,
+fn default_input_buffer() -> usize {
+    DEFAULT_INPUT_BUFFER
+}
+
+// Options and maps from before the grid had two sides have a single `gridSize` for both.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WithGridSize<T> {
+    #[serde(default)]
+    grid_size: Option<i32>,
+    #[serde(flatten)]
+    value: T,
+}
+
+impl<T> WithGridSize<T> {
+    // Sides that were given win over the old size.
+    pub(crate) fn into_value(self, sides: impl FnOnce(&mut T) -> [&mut i32; 2]) -> T {
+        let mut value = self.value;
+
+        if let Some(size) = self.grid_size {
+            for side in sides(&mut value).into_iter().filter(|side| **side == 0) {
+                *side = size;
+            }
+        }
+
+        value
+    }
+}
+
+impl Serialize for GameOptions {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameOptions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameOptions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Options(#[serde(with = "GameOptions")] GameOptions);
+
+        let options = WithGridSize::<Options>::deserialize(deserializer)?;
+        Ok(options.into_value(|Options(options)| [&mut options.grid_width, &mut options.grid_height]).0)
+    }
+}
+
+// A step of the difficulty curve, reached once the best score gets to `score`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Level {
+    pub score: u32,
+    // How many times faster than `fps` the game runs from this level on.
+    #[serde(default = "default_level_speed")]
+    pub speed: f64,
+    // Obstacles added to free cells when the level is reached.
+    #[serde(default)]
+    pub obstacles: u32,
+    // How much food is on the board from this level on, unchanged when missing.
+    #[serde(default)]
+    pub food_count: Option<u32>,
+}
+
+fn default_level_speed() -> f64 {
+    1.0
+}
+
+impl GameOptions {
+    // A small single player game for tests to start from and override what they care about.
+    #[cfg(test)]
+    pub fn test_default() -> Self {
+        GameOptions {
+            id: "".into(),
+            snake_color: "#00FF00".into(),
+            difficulty: Difficulty::Easy,
+            grid_width: 20,
+            grid_height: 20,
+            food_count: 3,
+            fps: 10,
+            seed: None,
+            boundary_mode: BoundaryMode::Wrap,
+            ai_strategy: AiStrategy::Greedy,
+            players: vec![],
+            input_buffer: DEFAULT_INPUT_BUFFER,
+            food_weights: FoodWeights::default(),
+            levels: vec![],
+        }
+    }
+
+    pub fn grid(&self) -> GridSize {
+        GridSize::new(self.grid_width, self.grid_height)
+    }
+
+    // At most a quarter of the board can be food, so there is always room to move and to place more.
+    pub fn max_food_count(grid: GridSize) -> u32 {
+        grid.area() as u32 / 4
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<OptionsError>> {
+        let mut errors = vec![];
+
+        if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&self.grid_width) {
+            errors.push(OptionsError::GridWidthOutOfRange(self.grid_width));
+        }
+
+        if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&self.grid_height) {
+            errors.push(OptionsError::GridHeightOutOfRange(self.grid_height));
+        }
+
+        let max_food_count = Self::max_food_count(self.grid().clamped());
+
+        if !(1..=max_food_count).contains(&self.food_count) {
+            errors.push(OptionsError::FoodCountOutOfRange(self.food_count, max_food_count));
+        }
+
+        if !(MIN_FPS..=MAX_FPS).contains(&self.fps) {
+            errors.push(OptionsError::FpsOutOfRange(self.fps));
+        }
+
+        if !(1..=MAX_INPUT_BUFFER).contains(&self.input_buffer) {
+            errors.push(OptionsError::InputBufferOutOfRange(self.input_buffer));
+        }
+
+        if self.players.len() > MAX_PLAYERS {
+            errors.push(OptionsError::TooManyPlayers(self.players.len()));
+        }
+
+        if self.players.is_empty() && self.snake_color.parse::<Color>().is_err() {
+            errors.push(OptionsError::InvalidSnakeColor(self.snake_color.clone()));
+        }
+
+        for (index, player) in self.players.iter().enumerate() {
+            if player.color.parse::<Color>().is_err() {
+                errors.push(OptionsError::InvalidPlayerColor(index, player.color.clone()));
+            }
+
+            let Some(keys) = player.controller.key_bindings() else { continue };
+            let earlier = self.players[..index].iter().position(|other| other.controller.key_bindings().is_some_and(|other| other.overlaps(keys)));
+
+            if let Some(other) = earlier {
+                errors.push(OptionsError::KeyBindingsOverlap(index, other));
+            }
+        }
+
+        if self.food_weights.total() == 0 {
+            errors.push(OptionsError::NoFoodWeights);
+        }
+
+        for (index, level) in self.levels.iter().enumerate() {
+            if index > 0 && level.score <= self.levels[index - 1].score {
+                errors.push(OptionsError::LevelScoreNotIncreasing(index, level.score));
+            }
+
+            if !(MIN_LEVEL_SPEED..=MAX_LEVEL_SPEED).contains(&level.speed) {
+                errors.push(OptionsError::LevelSpeedOutOfRange(index, level.speed));
+            }
+
+            if let Some(food_count) = level.food_count.filter(|count| !(1..=max_food_count).contains(count)) {
+                errors.push(OptionsError::LevelFoodCountOutOfRange(index, food_count, max_food_count));
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    // Pulls every number back into its range, so options that skipped `validate` cannot break the game.
+    // Colors are left alone, there is no sensible color to fall back to.
+    pub fn normalized(mut self) -> Self {
+        self.grid_width = self.grid_width.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
+        self.grid_height = self.grid_height.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE);
+        self.food_count = self.food_count.clamp(1, Self::max_food_count(self.grid()));
+        self.fps = self.fps.clamp(MIN_FPS, MAX_FPS);
+        self.input_buffer = self.input_buffer.clamp(1, MAX_INPUT_BUFFER);
+        self.players.truncate(MAX_PLAYERS);
+
+        if self.food_weights.total() == 0 {
+            self.food_weights = FoodWeights::default();
+        }
+
+        let max_food_count = Self::max_food_count(self.grid());
+        self.levels.sort_by_key(|level| level.score);
+        self.levels.dedup_by_key(|level| level.score);
+
+        for level in &mut self.levels {
+            level.speed = if level.speed.is_nan() { 1.0 } else { level.speed.clamp(MIN_LEVEL_SPEED, MAX_LEVEL_SPEED) };
+            level.food_count = level.food_count.map(|count| count.clamp(1, max_food_count));
+        }
+
+        self
+    }
+
+    // Without explicit players the game has a single human snake in `snake_color`.
+    pub fn player_options(&self) -> Vec<PlayerOptions> {
+        if self.players.is_empty() {
+            return vec![PlayerOptions {
+                color: self.snake_color.clone(),
+                controller: PlayerController::default(),
+            }];
+        }
+
+        self.players.clone()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum OptionsError {
+    GridWidthOutOfRange(i32),
+    GridHeightOutOfRange(i32),
+    FoodCountOutOfRange(u32, u32),
+    FpsOutOfRange(i32),
+    InputBufferOutOfRange(usize),
+    TooManyPlayers(usize),
+    InvalidSnakeColor(String),
+    InvalidPlayerColor(usize, String),
+    NoFoodWeights,
+    LevelScoreNotIncreasing(usize, u32),
+    LevelSpeedOutOfRange(usize, f64),
+    LevelFoodCountOutOfRange(usize, u32, u32),
+    KeyBindingsOverlap(usize, usize),
+}
+
+impl OptionsError {
+    // The option the error is about, named as in the JSON options.
+    pub fn field(&self) -> String {
+        match self {
+            OptionsError::GridWidthOutOfRange(_) => "gridWidth".into(),
+            OptionsError::GridHeightOutOfRange(_) => "gridHeight".into(),
+            OptionsError::FoodCountOutOfRange(..) => "foodCount".into(),
+            OptionsError::FpsOutOfRange(_) => "fps".into(),
+            OptionsError::InputBufferOutOfRange(_) => "inputBuffer".into(),
+            OptionsError::TooManyPlayers(_) => "players".into(),
+            OptionsError::InvalidSnakeColor(_) => "snakeColor".into(),
+            OptionsError::InvalidPlayerColor(index, _) => format!("players[{}].color", index),
+            OptionsError::NoFoodWeights => "foodWeights".into(),
+            OptionsError::LevelScoreNotIncreasing(index, _) => format!("levels[{}].score", index),
+            OptionsError::LevelSpeedOutOfRange(index, _) => format!("levels[{}].speed", index),
+            OptionsError::LevelFoodCountOutOfRange(index, ..) => format!("levels[{}].foodCount", index),
+            OptionsError::KeyBindingsOverlap(index, _) => format!("players[{}].controller.keys", index),
+        }
+    }
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::GridWidthOutOfRange(width) => write!(f, "gridWidth must be between {} and {}, got {}", MIN_GRID_SIZE, MAX_GRID_SIZE, width),
+            OptionsError::GridHeightOutOfRange(height) => write!(f, "gridHeight must be between {} and {}, got {}", MIN_GRID_SIZE, MAX_GRID_SIZE, height),
+            OptionsError::FoodCountOutOfRange(count, max) => write!(f, "foodCount must be between 1 and {}, got {}", max, count),
+            OptionsError::FpsOutOfRange(fps) => write!(f, "fps must be between {} and {}, got {}", MIN_FPS, MAX_FPS, fps),
+            OptionsError::InputBufferOutOfRange(size) => write!(f, "inputBuffer must be between 1 and {}, got {}", MAX_INPUT_BUFFER, size),
+            OptionsError::TooManyPlayers(count) => write!(f, "at most {} players can play, got {}", MAX_PLAYERS, count),
+            OptionsError::InvalidSnakeColor(color) => write!(f, "snakeColor {:?} is not a valid color", color),
+            OptionsError::InvalidPlayerColor(index, color) => write!(f, "players[{}].color {:?} is not a valid color", index, color),
+            OptionsError::NoFoodWeights => write!(f, "foodWeights must give at least one kind of food a weight"),
+            OptionsError::LevelScoreNotIncreasing(index, score) => write!(f, "levels[{}].score must be higher than the level before, got {}", index, score),
+            OptionsError::LevelSpeedOutOfRange(index, speed) => write!(f, "levels[{}].speed must be between {} and {}, got {}", index, MIN_LEVEL_SPEED, MAX_LEVEL_SPEED, speed),
+            OptionsError::LevelFoodCountOutOfRange(index, count, max) => write!(f, "levels[{}].foodCount must be between 1 and {}, got {}", index, max, count),
+            OptionsError::KeyBindingsOverlap(index, other) => write!(f, "players[{}].controller.keys share keys with players[{}]", index, other),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerOptions {
+    pub color: String,
+    #[serde(default)]
+    pub controller: PlayerController
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlayerController {
+    Human {
+        #[serde(default)]
+        keys: KeyBindings
+    },
+    Ai {
+        #[serde(default)]
+        strategy: AiStrategy
+    }
+}
+
+impl Default for PlayerController {
+    fn default() -> Self {
+        PlayerController::Human { keys: KeyBindings::default() }
+    }
+}
+
+impl PlayerController {
+    pub fn key_bindings(&self) -> Option<KeyBindings> {
+        match self {
+            PlayerController::Human { keys } => Some(*keys),
+            PlayerController::Ai { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyBindings {
+    #[default]
+    All,
+    Wasd,
+    Arrows
+}
+
+impl KeyBindings {
+    // `All` listens to both sets, so it shares keys with every binding.
+    pub fn overlaps(self, other: KeyBindings) -> bool {
+        self == other || self == KeyBindings::All || other == KeyBindings::All
+    }
+}
+
+// What a drawn cell belongs to, so the renderer can tell them apart.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CellKind {
+    Snake,
+    Food,
+    Obstacle,
+}
+
+// A single cell to draw. Snakes sit between cells while they move, so the position is fractional.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CellInstance {
+    pub position: (f32, f32),
+    pub color: [f32; 4],
+    pub kind: CellKind,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
@@ -35,7 +430,108 @@ pub enum Difficulty {
     Hard
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum BoundaryMode {
+    #[default]
+    Wrap,
+    Solid,
+    WrapHorizontal,
+    WrapVertical
+}
+
+impl BoundaryMode {
+    pub fn wraps_horizontally(&self) -> bool {
+        matches!(self, BoundaryMode::Wrap | BoundaryMode::WrapHorizontal)
+    }
+
+    pub fn wraps_vertically(&self) -> bool {
+        matches!(self, BoundaryMode::Wrap | BoundaryMode::WrapVertical)
+    }
+
+    // Maps a position that may have left the grid back onto it,
+    // or returns None when it crossed a solid edge.
+    pub fn normalize(&self, position: (i32, i32), grid: GridSize) -> Option<(i32, i32)> {
+        let (mut x, mut y) = position;
+
+        if self.wraps_horizontally() {
+            x = x.rem_euclid(grid.width);
+        }
+
+        if self.wraps_vertically() {
+            y = y.rem_euclid(grid.height);
+        }
+
+        grid.contains((x, y)).then_some((x, y))
+    }
+
+    // Shortest distance along one axis, taking the wrap-around edge into account.
+    pub fn axis_distance(wraps: bool, from: i32, to: i32, length: i32) -> i32 {
+        let distance = (from - to).abs();
+
+        if wraps {
+            distance.min(length - distance)
+        }
+        else {
+            distance
+        }
+    }
+
+    pub fn distance(&self, from: (i32, i32), to: (i32, i32), grid: GridSize) -> i32 {
+        Self::axis_distance(self.wraps_horizontally(), from.0, to.0, grid.width)
+            + Self::axis_distance(self.wraps_vertically(), from.1, to.1, grid.height)
+    }
+}
+
+// The board's size in cells. Cells are numbered from (0, 0) in the bottom left corner.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GridSize {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl GridSize {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn square(size: i32) -> Self {
+        Self::new(size, size)
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
+    pub fn area(&self) -> usize {
+        self.width.max(0) as usize * self.height.max(0) as usize
+    }
+
+    // Every cell, column by column from the bottom left corner.
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> + use<> {
+        let GridSize { width, height } = *self;
+        (0..width).flat_map(move |x| (0..height).map(move |y| (x, y)))
+    }
+
+    // Both sides pulled into the range the options allow.
+    pub fn clamped(&self) -> Self {
+        Self::new(self.width.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE), self.height.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum AiStrategy {
+    #[default]
+    Greedy,
+    AStar,
+    Hamiltonian,
+    TailChasing,
+    Survival
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
 pub enum Direction {
     Up,
     Down,
@@ -43,9 +539,312 @@ pub enum Direction {
     Right
 }
 
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum GameResult {
     Noop,
     Score,
     Over
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum FoodKind {
+    #[default]
+    Regular,
+    Bonus,
+    // Vanishes when it is not eaten within `lifetime` ticks.
+    Golden,
+    Shrink,
+    SpeedUp,
+    SlowDown,
+    Poison
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 7] = [
+        FoodKind::Regular,
+        FoodKind::Bonus,
+        FoodKind::Golden,
+        FoodKind::Shrink,
+        FoodKind::SpeedUp,
+        FoodKind::SlowDown,
+        FoodKind::Poison,
+    ];
+
+    pub fn points(&self) -> u32 {
+        match self {
+            FoodKind::Bonus => 3,
+            FoodKind::Golden => 5,
+            FoodKind::Poison => 0,
+            _ => 1,
+        }
+    }
+
+    // Segments gained, or lost when negative.
+    pub fn growth(&self) -> i32 {
+        match self {
+            FoodKind::Shrink => -3,
+            FoodKind::Poison => 0,
+            _ => 1,
+        }
+    }
+
+    pub fn lifetime(&self) -> Option<u32> {
+        match self {
+            FoodKind::Golden => Some(40),
+            _ => None,
+        }
+    }
+
+    // How much faster the game runs for a while after eating it.
+    pub fn speed_multiplier(&self) -> Option<f64> {
+        match self {
+            FoodKind::SpeedUp => Some(1.5),
+            FoodKind::SlowDown => Some(0.6),
+            _ => None,
+        }
+    }
+
+    // Regular food keeps the random color it spawned with, the others look the same every time.
+    pub fn color(&self) -> Option<[f32; 4]> {
+        match self {
+            FoodKind::Regular => None,
+            FoodKind::Bonus => Some([0.2, 0.6, 1.0, 1.0]),
+            FoodKind::Golden => Some([1.0, 0.84, 0.0, 1.0]),
+            FoodKind::Shrink => Some([1.0, 0.5, 0.8, 1.0]),
+            FoodKind::SpeedUp => Some([1.0, 0.3, 0.1, 1.0]),
+            FoodKind::SlowDown => Some([0.4, 0.9, 0.9, 1.0]),
+            FoodKind::Poison => Some([0.5, 0.1, 0.6, 1.0]),
+        }
+    }
+}
+
+// How often each kind of food spawns, relative to the others. Only regular food by default.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FoodWeights {
+    pub regular: u32,
+    pub bonus: u32,
+    pub golden: u32,
+    pub shrink: u32,
+    pub speed_up: u32,
+    pub slow_down: u32,
+    pub poison: u32
+}
+
+impl Default for FoodWeights {
+    fn default() -> Self {
+        Self {
+            regular: 1,
+            bonus: 0,
+            golden: 0,
+            shrink: 0,
+            speed_up: 0,
+            slow_down: 0,
+            poison: 0,
+        }
+    }
+}
+
+impl FoodWeights {
+    pub fn weight(&self, kind: FoodKind) -> u32 {
+        match kind {
+            FoodKind::Regular => self.regular,
+            FoodKind::Bonus => self.bonus,
+            FoodKind::Golden => self.golden,
+            FoodKind::Shrink => self.shrink,
+            FoodKind::SpeedUp => self.speed_up,
+            FoodKind::SlowDown => self.slow_down,
+            FoodKind::Poison => self.poison,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        FoodKind::ALL.iter().map(|&kind| self.weight(kind)).sum()
+    }
+
+    // The only kind that can spawn, when there is just one.
+    pub fn single_kind(&self) -> Option<FoodKind> {
+        let mut kinds = FoodKind::ALL.into_iter().filter(|&kind| self.weight(kind) > 0);
+
+        match (kinds.next(), kinds.next()) {
+            (Some(kind), None) => Some(kind),
+            _ => None,
+        }
+    }
+
+    // The kind at `roll`, a number below `total`, with each kind covering as many numbers as its weight.
+    pub fn kind_at(&self, mut roll: u32) -> FoodKind {
+        for kind in FoodKind::ALL {
+            let weight = self.weight(kind);
+
+            if roll < weight {
+                return kind;
+            }
+
+            roll -= weight;
+        }
+
+        FoodKind::Regular
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GameStats {
+    pub score: u32,
+    pub length: usize,
+    pub max_length: usize,
+    pub ticks: u32,
+    pub foods_eaten: BTreeMap<FoodKind, u32>,
+    pub is_ai: bool
+}
+
+impl GameStats {
+    pub fn new(length: usize, is_ai: bool) -> Self {
+        Self {
+            length,
+            max_length: length,
+            is_ai,
+            ..Self::default()
+        }
+    }
+
+    pub fn record_food(&mut self, kind: FoodKind, length: usize) {
+        self.score += kind.points();
+        *self.foods_eaten.entry(kind).or_default() += 1;
+        self.set_length(length);
+    }
+
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length;
+        self.max_length = self.max_length.max(length);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> GameOptions {
+        GameOptions::test_default()
+    }
+
+    #[test]
+    fn reports_every_field_out_of_range() {
+        let options = GameOptions {
+            grid_width: 0,
+            grid_height: 200,
+            food_count: 500,
+            fps: 0,
+            input_buffer: 0,
+            snake_color: "nope".into(),
+            food_weights: FoodWeights { regular: 0, ..FoodWeights::default() },
+            ..options()
+        };
+
+        let fields: Vec<String> = options.validate().unwrap_err().iter().map(OptionsError::field).collect();
+
+        assert_eq!(fields, ["gridWidth", "gridHeight", "foodCount", "fps", "inputBuffer", "snakeColor", "foodWeights"]);
+        assert_eq!(self::options().validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_players_sharing_keys() {
+        let player = |keys| PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys } };
+        let ai = PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Ai { strategy: AiStrategy::Greedy } };
+        let options = GameOptions {
+            players: vec![player(KeyBindings::Wasd), ai.clone(), player(KeyBindings::All), player(KeyBindings::Wasd)],
+            ..options()
+        };
+
+        assert_eq!(options.validate(), Err(vec![OptionsError::KeyBindingsOverlap(2, 0), OptionsError::KeyBindingsOverlap(3, 0)]));
+
+        let options = GameOptions { players: vec![player(KeyBindings::Wasd), ai, player(KeyBindings::Arrows)], ..self::options() };
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn reads_the_old_grid_size_as_both_sides() {
+        let json = serde_json::to_value(options()).unwrap();
+        assert_eq!(serde_json::from_value::<GameOptions>(json.clone()).unwrap(), options(), "Options should survive a round trip");
+
+        let mut old = json;
+        let fields = old.as_object_mut().unwrap();
+        fields.remove("gridWidth");
+        fields.remove("gridHeight");
+        fields.insert("gridSize".into(), 30.into());
+
+        let options = serde_json::from_value::<GameOptions>(old).unwrap();
+        assert_eq!(options.grid(), GridSize::square(30));
+    }
+
+    #[test]
+    fn normalizes_into_range() {
+        let options = GameOptions {
+            grid_width: 0,
+            grid_height: 12,
+            food_count: 500,
+            fps: 1000,
+            input_buffer: 50,
+            food_weights: FoodWeights { regular: 0, ..FoodWeights::default() },
+            ..options()
+        }.normalized();
+
+        assert_eq!(options.grid(), GridSize::new(MIN_GRID_SIZE, 12));
+        assert_eq!(options.food_count, 24, "A quarter of the 8x12 board");
+        assert_eq!(options.fps, MAX_FPS);
+        assert_eq!(options.input_buffer, MAX_INPUT_BUFFER);
+        assert_eq!(options.food_weights, FoodWeights::default(), "Food weights of zero fall back to regular food");
+        assert_eq!(options.clone().normalized(), options);
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn food_weights_cover_every_roll() {
+        let weights = FoodWeights { regular: 2, golden: 1, poison: 3, ..FoodWeights::default() };
+
+        let kinds: Vec<FoodKind> = (0..weights.total()).map(|roll| weights.kind_at(roll)).collect();
+
+        assert_eq!(kinds, [FoodKind::Regular, FoodKind::Regular, FoodKind::Golden, FoodKind::Poison, FoodKind::Poison, FoodKind::Poison]);
+        assert_eq!(weights.single_kind(), None);
+        assert_eq!(FoodWeights::default().single_kind(), Some(FoodKind::Regular));
+    }
+
+    #[test]
+    fn checks_and_orders_levels() {
+        let level = |score, speed, food_count| Level { score, speed, obstacles: 0, food_count };
+        let options = GameOptions {
+            levels: vec![level(10, 1.5, None), level(5, 10.0, Some(500)), level(10, f64::NAN, None)],
+            ..options()
+        };
+
+        let fields: Vec<String> = options.validate().unwrap_err().iter().map(OptionsError::field).collect();
+        assert_eq!(fields, ["levels[1].score", "levels[1].speed", "levels[1].foodCount", "levels[2].speed"]);
+
+        let options = options.normalized();
+        assert_eq!(options.levels, [level(5, MAX_LEVEL_SPEED, Some(100)), level(10, 1.5, None)]);
+        assert_eq!(options.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{abstractions::Transport, error::GameError, events::GameEvent, game::Game, models::Direction, randomizer::Randomizer};
+
+use super::{ClientMessage, InputCommand, ServerMessage};
+
+// The client side of a networked game. The local game keeps running between server updates,
+// and every snapshot puts it back in line with the server before replaying turns still in flight.
+pub struct GameClient<T: Transport<ClientMessage, ServerMessage>> {
+    transport: T,
+    player: Option<usize>,
+    next_sequence: u32,
+    // Turns the server has not acknowledged yet, in the order they were made and so by tick.
+    pending: Vec<InputCommand>,
+}
+
+impl<T: Transport<ClientMessage, ServerMessage>> GameClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            player: None,
+            next_sequence: 0,
+            pending: vec![],
+        }
+    }
+
+    // The player the server seated this client at, once it has been welcomed.
+    pub fn player(&self) -> Option<usize> {
+        self.player
+    }
+
+    pub fn send_input(&mut self, tick: u32, direction: Direction) -> Result<(), GameError> {
+        let Some(player) = self.player else {
+            return Ok(());
+        };
+
+        let input = InputCommand {
+            sequence: self.next_sequence,
+            tick,
+            player,
+            direction,
+        };
+
+        self.next_sequence += 1;
+        self.pending.push(input);
+        self.transport.send(ClientMessage::Input(input))
+    }
+
+    // Applies everything the server sent since the last call and returns the server's events.
+    pub fn sync<R: Randomizer>(&mut self, game: &mut Game<R>) -> Result<Vec<GameEvent>, GameError> {
+        let mut events = vec![];
+
+        while let Some(message) = self.transport.receive() {
+            match message {
+                ServerMessage::Welcome { player, options, map } => {
+                    match map {
+                        Some(map) => game.load_map(map)?,
+                        None if game.map().is_some() => game.unload_map()?,
+                        None => {}
+                    }
+
+                    game.apply_options_and_reset(options)?;
+                    self.player = Some(player);
+                    self.pending.clear();
+                }
+                ServerMessage::Snapshot { snapshot, last_input } => {
+                    self.pending.retain(|input| last_input.is_none_or(|last| input.sequence > last));
+
+                    let predicted_tick = game.tick();
+                    game.restore(&snapshot);
+                    self.replay_pending(game, predicted_tick);
+                }
+                ServerMessage::Events { events: server_events } => events.extend(server_events),
+            }
+        }
+
+        Ok(events)
+    }
+
+    // Steps the restored game back up to the tick it had predicted, making each pending turn
+    // at the tick it was made on, the same way the server applies it.
+    fn replay_pending<R: Randomizer>(&self, game: &mut Game<R>, predicted_tick: u32) {
+        let Some(player) = self.player else {
+            return;
+        };
+
+        let mut inputs = self.pending.iter().peekable();
+
+        loop {
+            while let Some(input) = inputs.next_if(|input| input.tick <= game.tick()) {
+                game.change_player_direction(player, input.direction);
+            }
+
+            if game.tick() >= predicted_tick {
+                break;
+            }
+
+            game.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::{AiStrategy, Difficulty, GameOptions, KeyBindings, PlayerController, PlayerOptions}, net::{loopback, GameServer, LoopbackTransport}, randomizer::SeededRandomizer};
+
+    type TestServer = GameServer<SeededRandomizer, LoopbackTransport<ServerMessage, ClientMessage>>;
+    type TestClient = GameClient<LoopbackTransport<ClientMessage, ServerMessage>>;
+
+    fn options() -> GameOptions {
+        GameOptions {
+            difficulty: Difficulty::Hard,
+            players: vec![
+                PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys: KeyBindings::Wasd } },
+                PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Ai { strategy: AiStrategy::Survival } },
+            ],
+            ..GameOptions::test_default()
+        }
+    }
+
+    fn connect() -> (TestServer, TestClient, Game<SeededRandomizer>) {
+        let mut server = GameServer::new(options(), None, SeededRandomizer::new(11)).unwrap();
+        let (transport, connection) = loopback();
+        server.connect(connection);
+
+        let client = GameClient::new(transport);
+        let mut game = Game::new(GameOptions::default(), SeededRandomizer::new(99));
+        game.apply_options_and_reset(GameOptions { players: vec![], ..options() }).unwrap();
+
+        (server, client, game)
+    }
+
+    #[test]
+    fn follows_the_server() {
+        let (mut server, mut client, mut game) = connect();
+        client.sync(&mut game).unwrap();
+
+        assert_eq!(client.player(), Some(0));
+        assert_eq!(game.players.len(), 2, "The welcome should bring the server's players");
+        assert_eq!(game.snapshot(), server.game().snapshot(), "The client should start from the server's board");
+
+        for (tick, direction) in [(0, Direction::Up), (3, Direction::Left), (6, Direction::Down)] {
+            while game.tick() < tick {
+                game.update();
+                server.tick();
+                client.sync(&mut game).unwrap();
+            }
+
+            client.send_input(game.tick(), direction).unwrap();
+            game.change_direction(direction);
+        }
+
+        server.tick();
+        client.sync(&mut game).unwrap();
+
+        assert_eq!(game.snapshot(), server.game().snapshot());
+        assert_eq!(server.game().players[0].direction, Direction::Down);
+    }
+
+    #[test]
+    fn replays_turns_the_server_has_not_seen() {
+        let (mut server, mut client, mut game) = connect();
+        client.sync(&mut game).unwrap();
+
+        server.tick();
+        client.send_input(game.tick(), Direction::Up).unwrap();
+        client.sync(&mut game).unwrap();
+
+        assert_eq!(server.game().players[0].direction, Direction::Right);
+        assert_eq!(game.players[0].heading(), Direction::Up, "The local turn should survive the older snapshot");
+        assert_eq!(game.players[0].snake.body(), server.game().players[0].snake.body());
+    }
+
+    #[test]
+    fn replays_turns_on_the_ticks_they_were_made() {
+        let (mut server, mut client, mut game) = connect();
+        client.sync(&mut game).unwrap();
+
+        client.send_input(game.tick(), Direction::Up).unwrap();
+        game.change_direction(Direction::Up);
+        game.update();
+        game.update();
+        client.send_input(game.tick(), Direction::Left).unwrap();
+        game.change_direction(Direction::Left);
+        game.update();
+
+        server.tick();
+        client.sync(&mut game).unwrap();
+
+        assert_eq!(game.tick(), 3, "The client should catch back up to its own tick");
+
+        server.tick();
+        server.tick();
+
+        assert_eq!(server.game().tick(), 3);
+        assert_eq!(game.players[0].snake.body(), server.game().players[0].snake.body(), "The replay should predict what the server does");
+    }
+}
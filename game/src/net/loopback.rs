@@ -0,0 +1,56 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use crate::{abstractions::Transport, error::GameError};
+
+// An in-process connection for tests and local servers: both ends share two queues.
+pub struct LoopbackTransport<S, R> {
+    outgoing: Rc<RefCell<VecDeque<S>>>,
+    incoming: Rc<RefCell<VecDeque<R>>>,
+}
+
+pub fn loopback<A, B>() -> (LoopbackTransport<A, B>, LoopbackTransport<B, A>) {
+    let forward = Rc::new(RefCell::new(VecDeque::new()));
+    let backward = Rc::new(RefCell::new(VecDeque::new()));
+
+    let first = LoopbackTransport {
+        outgoing: forward.clone(),
+        incoming: backward.clone(),
+    };
+    let second = LoopbackTransport {
+        outgoing: backward,
+        incoming: forward,
+    };
+
+    (first, second)
+}
+
+impl<S, R> Transport<S, R> for LoopbackTransport<S, R> {
+    fn send(&mut self, message: S) -> Result<(), GameError> {
+        self.outgoing.borrow_mut().push_back(message);
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Option<R> {
+        self.incoming.borrow_mut().pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivers_messages_in_order_to_the_other_end() {
+        let (mut client, mut server) = loopback::<u32, &str>();
+
+        client.send(1).unwrap();
+        client.send(2).unwrap();
+        server.send("pong").unwrap();
+
+        assert_eq!(server.receive(), Some(1));
+        assert_eq!(server.receive(), Some(2));
+        assert_eq!(server.receive(), None);
+        assert_eq!(client.receive(), Some("pong"));
+    }
+}
@@ -0,0 +1,9 @@
+mod protocol;
+mod server;
+mod client;
+mod loopback;
+
+pub use protocol::{ClientMessage, FoodSnapshot, GameSnapshot, InputCommand, PlayerSnapshot, ServerMessage, SpeedEffect};
+pub use server::GameServer;
+pub use client::GameClient;
+pub use loopback::{loopback, LoopbackTransport};
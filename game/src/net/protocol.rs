@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{events::GameEvent, map::GameMap, models::{Direction, FoodKind, GameOptions, GameStats}};
+
+// Everything a client needs to draw the board and keep simulating it from `tick`.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSnapshot {
+    pub tick: u32,
+    pub players: Vec<PlayerSnapshot>,
+    pub foods: Vec<FoodSnapshot>,
+    pub obstacles: Vec<Vec<(i32, i32)>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_effect: Option<SpeedEffect>,
+    #[serde(default)]
+    pub level: u32,
+}
+
+// A temporary change of game speed from eating speed-up or slow-down food.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedEffect {
+    pub multiplier: f64,
+    pub ticks_left: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSnapshot {
+    pub body: Vec<(i32, i32)>,
+    pub direction: Direction,
+    #[serde(default)]
+    pub turns: Vec<Direction>,
+    pub alive: bool,
+    pub stats: GameStats,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FoodSnapshot {
+    pub position: (i32, i32),
+    pub kind: FoodKind,
+    pub color: [f32; 4],
+    // Ticks left before the food disappears, for kinds that expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lifetime: Option<u32>,
+}
+
+// A turn made by a client. `sequence` counts up per client so the server can acknowledge it,
+// `tick` is the client's tick when the key was pressed, and the server applies it on that tick.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct InputCommand {
+    pub sequence: u32,
+    pub tick: u32,
+    pub player: usize,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ClientMessage {
+    Input(InputCommand),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ServerMessage {
+    Welcome {
+        player: usize,
+        options: GameOptions,
+        map: Option<GameMap>,
+    },
+    Snapshot {
+        snapshot: GameSnapshot,
+        // Sequence of the last input from this client that the snapshot includes.
+        last_input: Option<u32>,
+    },
+    Events {
+        events: Vec<GameEvent>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameOverReason;
+
+    #[test]
+    fn messages_survive_serialization() {
+        let messages = vec![
+            ServerMessage::Snapshot {
+                snapshot: GameSnapshot {
+                    tick: 7,
+                    players: vec![PlayerSnapshot {
+                        body: vec![(3, 2), (2, 2)],
+                        direction: Direction::Right,
+                        turns: vec![Direction::Up],
+                        alive: true,
+                        stats: GameStats::new(2, false),
+                    }],
+                    foods: vec![
+                        FoodSnapshot { position: (5, 5), kind: FoodKind::Regular, color: [1.0, 0.5, 0.0, 1.0], lifetime: None },
+                        FoodSnapshot { position: (6, 1), kind: FoodKind::Golden, color: [1.0, 0.5, 0.0, 1.0], lifetime: Some(12) },
+                    ],
+                    obstacles: vec![vec![(8, 8), (8, 9)]],
+                    speed_effect: Some(SpeedEffect { multiplier: 1.5, ticks_left: 9 }),
+                    level: 2,
+                },
+                last_input: Some(3),
+            },
+            ServerMessage::Events {
+                events: vec![GameEvent::MatchOver { winner: None, players: vec![] }, GameEvent::PlayerDied {
+                    player: 1,
+                    reason: GameOverReason::HeadToBody,
+                    stats: GameStats::default(),
+                }],
+            },
+        ];
+
+        for message in messages {
+            let json = serde_json::to_string(&message).unwrap();
+            assert_eq!(serde_json::from_str::<ServerMessage>(&json).unwrap(), message);
+        }
+
+        let input = ClientMessage::Input(InputCommand { sequence: 1, tick: 4, player: 0, direction: Direction::Up });
+        let json = serde_json::to_string(&input).unwrap();
+        assert_eq!(serde_json::from_str::<ClientMessage>(&json).unwrap(), input);
+    }
+}
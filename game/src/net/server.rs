@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+
+use log::warn;
+
+use crate::{abstractions::{AiController, Transport}, ai::create_ai_controller, error::GameError, game::Game, map::GameMap, models::{Direction, GameOptions, GameResult, PlayerController}, randomizer::Randomizer};
+
+use super::{ClientMessage, InputCommand, ServerMessage};
+
+struct Connection<T> {
+    player: usize,
+    transport: T,
+    last_input: Option<u32>,
+    // Turns made on a tick the server has not reached yet, held until it gets there.
+    scheduled: VecDeque<InputCommand>,
+}
+
+// Runs the authoritative game for remote players. Each tick applies the inputs that arrived,
+// steps the game and sends every client the resulting snapshot and events.
+pub struct GameServer<R: Randomizer, T: Transport<ServerMessage, ClientMessage>> {
+    game: Game<R>,
+    connections: Vec<Connection<T>>,
+    ais: Vec<Option<Box<dyn AiController>>>,
+}
+
+impl<R: Randomizer, T: Transport<ServerMessage, ClientMessage>> GameServer<R, T> {
+    pub fn new(options: GameOptions, map: Option<GameMap>, randomizer: R) -> Result<Self, GameError> {
+        let mut game = Game::new(options, randomizer);
+        game.initialize()?;
+
+        match map {
+            Some(map) => game.load_map(map)?,
+            None => game.reset(),
+        }
+
+        // AI players are simulated here so every client sees the same moves.
+        let ais = game.options().player_options().iter().map(|player| match player.controller {
+            PlayerController::Ai { strategy } => Some(create_ai_controller(strategy)),
+            PlayerController::Human { .. } => None,
+        }).collect();
+
+        Ok(Self {
+            game,
+            connections: vec![],
+            ais,
+        })
+    }
+
+    pub fn game(&self) -> &Game<R> {
+        &self.game
+    }
+
+    // Seats the client at the first human player nobody controls yet, or returns None when the game is full
+    // or the client cannot be reached.
+    pub fn connect(&mut self, mut transport: T) -> Option<usize> {
+        let player = self.game.players.iter().enumerate()
+            .position(|(index, player)| player.is_human() && !self.connections.iter().any(|connection| connection.player == index))?;
+
+        let welcome = transport.send(ServerMessage::Welcome {
+            player,
+            options: self.game.options().clone(),
+            map: self.game.map().cloned(),
+        }).and_then(|_| transport.send(ServerMessage::Snapshot {
+            snapshot: self.game.snapshot(),
+            last_input: None,
+        }));
+
+        if let Err(error) = welcome {
+            warn!("Could not welcome a client: {}", error);
+            return None;
+        }
+
+        self.connections.push(Connection {
+            player,
+            transport,
+            last_input: None,
+            scheduled: VecDeque::new(),
+        });
+
+        Some(player)
+    }
+
+    // Turns are applied on the tick the client made them, the way its prediction did.
+    // Turns arriving late are applied right away.
+    pub fn tick(&mut self) -> GameResult {
+        let tick = self.game.tick();
+
+        for connection in &mut self.connections {
+            while let Some(message) = connection.transport.receive() {
+                match message {
+                    // A client may only steer the player it was given.
+                    ClientMessage::Input(input) if input.player == connection.player => connection.scheduled.push_back(input),
+                    ClientMessage::Input(_) => {}
+                }
+            }
+
+            let due = connection.scheduled.iter().take_while(|input| input.tick <= tick).count();
+
+            for input in connection.scheduled.drain(..due) {
+                self.game.change_player_direction(connection.player, input.direction);
+                connection.last_input = Some(input.sequence);
+            }
+        }
+
+        for (player, direction) in self.ai_directions() {
+            self.game.change_player_direction(player, direction);
+        }
+
+        let result = self.game.update();
+        self.broadcast();
+
+        result
+    }
+
+    // Starts a new round for everyone, e.g. once a match is over.
+    pub fn restart(&mut self) {
+        self.game.reset();
+        self.broadcast();
+    }
+
+    fn ai_directions(&self) -> Vec<(usize, Direction)> {
+        let options = self.game.options();
+
+        self.game.players.iter().enumerate()
+            .filter(|(_, player)| player.is_alive())
+            .filter_map(|(index, player)| {
+                let controller = self.ais.get(index)?.as_ref()?;
+
+                controller.get_direction(
+                    &player.snake,
+                    &self.game.foods,
+                    &self.game.obstacles_for(index),
+                    options.grid(),
+                    options.boundary_mode)
+                    .map(|direction| (index, direction))
+            })
+            .collect()
+    }
+
+    // A client that can no longer be reached loses its seat, so someone else can take it.
+    fn broadcast(&mut self) {
+        let snapshot = self.game.snapshot();
+        let events = self.game.drain_events();
+
+        self.connections.retain_mut(|connection| {
+            let events_sent = if events.is_empty() { Ok(()) } else { connection.transport.send(ServerMessage::Events { events: events.clone() }) };
+
+            let sent = events_sent.and_then(|_| connection.transport.send(ServerMessage::Snapshot {
+                snapshot: snapshot.clone(),
+                last_input: connection.last_input,
+            }));
+
+            if let Err(error) = &sent {
+                warn!("Dropping the client of player {}: {}", connection.player, error);
+            }
+
+            sent.is_ok()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::{AiStrategy, BoundaryMode, KeyBindings, PlayerOptions}, net::{loopback, InputCommand, LoopbackTransport}, randomizer::SeededRandomizer};
+
+    type TestServer = GameServer<SeededRandomizer, LoopbackTransport<ServerMessage, ClientMessage>>;
+
+    fn versus_options() -> GameOptions {
+        GameOptions {
+            seed: Some(5),
+            boundary_mode: BoundaryMode::Solid,
+            players: vec![
+                PlayerOptions { color: "#00FF00".into(), controller: PlayerController::Human { keys: KeyBindings::Wasd } },
+                PlayerOptions { color: "#0000FF".into(), controller: PlayerController::Human { keys: KeyBindings::Arrows } },
+                PlayerOptions { color: "#FF0000".into(), controller: PlayerController::Ai { strategy: AiStrategy::Greedy } },
+            ],
+            ..GameOptions::test_default()
+        }
+    }
+
+    fn input(sequence: u32, player: usize, direction: Direction) -> ClientMessage {
+        ClientMessage::Input(InputCommand { sequence, tick: 0, player, direction })
+    }
+
+    #[test]
+    fn seats_clients_at_free_human_players() {
+        let mut server: TestServer = GameServer::new(versus_options(), None, SeededRandomizer::new(1)).unwrap();
+
+        let (_, first) = loopback();
+        let (_, second) = loopback();
+        let (_, third) = loopback();
+
+        assert_eq!(server.connect(first), Some(0));
+        assert_eq!(server.connect(second), Some(1));
+        assert_eq!(server.connect(third), None, "The AI player should not be handed out");
+    }
+
+    #[test]
+    fn clients_only_steer_their_own_player() {
+        let mut server: TestServer = GameServer::new(versus_options(), None, SeededRandomizer::new(1)).unwrap();
+        let (mut client, connection) = loopback();
+        server.connect(connection);
+
+        client.send(input(0, 1, Direction::Up)).unwrap();
+        client.send(input(1, 0, Direction::Down)).unwrap();
+        server.tick();
+
+        assert_eq!(server.game().players[0].direction, Direction::Down);
+        assert_eq!(server.game().players[1].direction, Direction::Right, "Other players should ignore the client");
+
+        let acknowledged = std::iter::from_fn(|| client.receive())
+            .filter_map(|message| match message {
+                ServerMessage::Snapshot { last_input, .. } => Some(last_input),
+                _ => None,
+            })
+            .last();
+        assert_eq!(acknowledged, Some(Some(1)));
+    }
+
+    #[test]
+    fn holds_turns_until_their_tick() {
+        let mut server: TestServer = GameServer::new(versus_options(), None, SeededRandomizer::new(1)).unwrap();
+        let (mut client, connection) = loopback();
+        server.connect(connection);
+
+        client.send(ClientMessage::Input(InputCommand { sequence: 0, tick: 1, player: 0, direction: Direction::Up })).unwrap();
+        server.tick();
+
+        assert_eq!(server.game().players[0].direction, Direction::Right, "The turn belongs to the next tick");
+
+        server.tick();
+
+        assert_eq!(server.game().players[0].direction, Direction::Up);
+        assert_eq!(server.game().players[0].snake.get_head_position().1, 6, "The snake should have turned on tick 1");
+    }
+}
@@ -1,39 +1,52 @@
+use crate::models::FoodKind;
 
+#[derive(Clone)]
 pub struct Food {
     pub position: (i32, i32),
-    cell_size: f32,
-    spacing: f32,
+    pub kind: FoodKind,
+    // Ticks left before the food disappears, for kinds that expire.
+    pub lifetime: Option<u32>,
     color: [f32; 4]
 }
 
 impl Food {
-    pub fn new(color: [f32; 4], position: (i32, i32), cell_size: f32) -> Self {
-        let spacing = 0.01;
+    pub fn new(color: [f32; 4], position: (i32, i32)) -> Self {
+        Self::of_kind(FoodKind::Regular, color, position)
+    }
 
+    pub fn of_kind(kind: FoodKind, color: [f32; 4], position: (i32, i32)) -> Self {
         Food { 
             position,
-            cell_size,
-            spacing,
+            kind,
+            lifetime: kind.lifetime(),
             color
         }
     }
 
-    pub fn as_vertices(&self) -> Vec<f32> {
-        let (x, y) = self.position;
-        let x1 = x as f32 * self.cell_size - 1.0 + self.spacing;
-        let y1 = y as f32 * self.cell_size - 1.0 + self.spacing;
-        let x2 = x1 + self.cell_size - self.spacing;
-        let y2 = y1 + self.cell_size - self.spacing;
-
-        let vertices: [f32; 36] = [
-            x1, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-            x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-            x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-            x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-            x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-            x2, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-        ];
-
-        vertices.to_vec()
+    // The color it spawned with, special kinds are drawn in their own color instead.
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    // Counts down the lifetime and returns whether the food has just expired.
+    pub fn tick(&mut self) -> bool {
+        match self.lifetime.as_mut() {
+            Some(lifetime) => {
+                *lifetime = lifetime.saturating_sub(1);
+                *lifetime == 0
+            }
+            None => false,
+        }
+    }
+
+    pub fn display_color(&self) -> [f32; 4] {
+        let mut color = self.kind.color().unwrap_or(self.color);
+
+        // Expiring food fades out over the last part of its lifetime.
+        if let (Some(left), Some(total)) = (self.lifetime, self.kind.lifetime()) {
+            color[3] *= (left as f32 / total as f32 * 2.0).clamp(0.25, 1.0);
+        }
+
+        color
     }
-}
\ No newline at end of file
+}
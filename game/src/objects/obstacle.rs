@@ -1,53 +1,44 @@
+use std::collections::BTreeSet;
+
+#[derive(Clone)]
 pub struct Obstacle {
-    pub position: (i32, i32),
-    cell_size: f32,
-    spacing: f32,
+    cells: BTreeSet<(i32, i32)>,
     color: [f32; 4],
 }
 
 impl Obstacle {
-    pub fn new(color: [f32; 4], position: (i32, i32), cell_size: f32) -> Self {
-        let spacing = 0.01;
+    pub fn new(color: [f32; 4], position: (i32, i32)) -> Self {
+        Self::rectangle(color, position, 2, 2)
+    }
+
+    pub fn rectangle(color: [f32; 4], position: (i32, i32), width: i32, height: i32) -> Self {
+        let (x, y) = position;
+        let cells = (x..x + width).flat_map(|i| (y..y + height).map(move |j| (i, j)));
+
+        Self::from_cells(color, cells)
+    }
 
+    pub fn from_cells(color: [f32; 4], cells: impl IntoIterator<Item = (i32, i32)>) -> Self {
         Obstacle {
-            position,
-            cell_size,
-            spacing,
+            cells: cells.into_iter().collect(),
             color,
         }
     }
 
-    pub fn as_vertices(&self) -> Vec<f32> {
-        let (x, y) = self.position;
-        let size = 2; // 2x2 block
-
-        let mut all_vertices = Vec::new();
-
-        for i in 0..size {
-            for j in 0..size {
-                let x1 = (x + i) as f32 * self.cell_size - 1.0 + self.spacing;
-                let y1 = (y + j) as f32 * self.cell_size - 1.0 + self.spacing;
-                let x2 = x1 + self.cell_size - self.spacing;
-                let y2 = y1 + self.cell_size - self.spacing;
-
-                let vertices: [f32; 36] = [
-                    x1, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                    x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                    x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-                    x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-                    x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                    x2, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-                ];
-
-                all_vertices.extend_from_slice(&vertices);
-            }
-        }
+    // The lowest cell, used as the obstacle's anchor in events.
+    pub fn position(&self) -> (i32, i32) {
+        self.cells.first().copied().unwrap_or_default()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.cells.iter().copied()
+    }
 
-        all_vertices
+    pub fn color(&self) -> [f32; 4] {
+        self.color
     }
 
     pub fn occupies(&self, position: (i32, i32)) -> bool {
-        let (x, y) = self.position;
-        position.0 >= x && position.0 < x + 2 && position.1 >= y && position.1 < y + 2
+        self.cells.contains(&position)
     }
 }
@@ -1,44 +1,61 @@
-use log::debug;
+use crate::models::Direction;
 
 pub struct Snake {
     body_length: usize,
+    spawn_position: (i32, i32),
+    spawn_direction: Direction,
     body: Vec<(i32, i32)>,
-    cell_size: f32,
-    spacing: f32,
+    // The body before the last move, which drawing slides away from.
+    previous_body: Vec<(i32, i32)>,
     color: [f32; 4],
 }
 
+impl Default for Snake {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Snake {
     pub fn new() -> Self {
-      
-        let spacing = 0.01;
-
         Snake {
             body: vec![],
-            cell_size: 0.0,
-            spacing,
+            previous_body: vec![],
             color: [0.0, 0.0, 0.0, 0.0],
             body_length: 0,
+            spawn_position: (10, 10),
+            spawn_direction: Direction::Right,
         }
     }
 
-    pub fn initialize(&mut self, body_length: usize, cell_size: f32) {
-        self.body = Self::initialize_body(body_length);
-        self.cell_size = cell_size;
+    pub fn initialize(&mut self, body_length: usize) {
+        self.body_length = body_length;
+        self.body = self.initialize_body();
+        self.previous_body = self.body.clone();
+    }
+
+    // Takes effect on the next reset.
+    pub fn set_spawn(&mut self, position: (i32, i32), direction: Direction, body_length: usize) {
+        self.spawn_position = position;
+        self.spawn_direction = direction;
         self.body_length = body_length;
     }
 
+    pub fn spawn_direction(&self) -> Direction {
+        self.spawn_direction
+    }
+
+    // The new segment sits on the tail and separates from it on the next move,
+    // so growing can never land the body on a wall or on the head.
     pub fn grow(&mut self) {
-        let length = self.body.len();
+        let tail = self.body[self.body.len() - 1];
+        self.body.push(tail);
+    }
 
-        let last = self.body[length - 1];
-        let second_last = self.body[length - 2];
-    
-        let dx = last.0 - second_last.0;
-        let dy = last.1 - second_last.1;
-    
-        let new_segment = (last.0 + dx, last.1 + dy);
-        self.body.push(new_segment);
+    // Drops up to `amount` segments off the tail, never going below `min_length`.
+    pub fn shrink(&mut self, amount: usize, min_length: usize) {
+        let length = self.body.len().saturating_sub(amount).max(min_length.max(1));
+        self.body.truncate(length);
     }
 
     pub fn is_self_collision(&self) -> bool {
@@ -54,26 +71,34 @@ impl Snake {
     }
 
     pub fn occupies(&self, position: (i32, i32)) -> bool {
-        self.body.iter().any(|&segment| segment == position)
+        self.body.contains(&position)
     }
 
     pub fn head_overlaps(&self, position: (i32, i32)) -> bool {
         self.body[0] == position
     }
 
+    // Replaces the whole body, head first, e.g. with one received from a server.
+    pub fn set_body(&mut self, body: Vec<(i32, i32)>) {
+        self.previous_body = body.clone();
+        self.body = body;
+    }
+
     pub fn set_color(&mut self, color: [f32; 4]) {
         self.color = color;
     }
 
-    pub fn resize(&mut self, cell_size: f32) {
-        self.cell_size = cell_size;
+    pub fn color(&self) -> [f32; 4] {
+        self.color
     }
 
     pub fn reset(&mut self) {
-        self.body = Self::initialize_body(self.body_length);
+        self.body = self.initialize_body();
+        self.previous_body = self.body.clone();
     }
 
     pub fn move_to(&mut self, new_head_position: (i32, i32)) {
+        self.previous_body.clone_from(&self.body);
 
         for i in (1..self.body.len()).rev() {
             self.body[i] = self.body[i - 1];
@@ -82,50 +107,102 @@ impl Snake {
         self.body[0] = new_head_position;
     }
 
+    pub fn length(&self) -> usize {
+        self.body.len()
+    }
+
     pub fn get_head_position(&self) -> (i32, i32) {
         self.body[0]
     }
 
+    pub fn get_tail_position(&self) -> (i32, i32) {
+        self.body[self.body.len() - 1]
+    }
+
+    pub fn body(&self) -> &[(i32, i32)] {
+        &self.body
+    }
+
+    // Number of moves until no segment covers `position` any more, 0 when it is already free.
+    pub fn moves_until_free(&self, position: (i32, i32)) -> usize {
+        self.body.iter()
+            .position(|&segment| segment == position)
+            .map_or(0, |index| self.body.len() - index)
+    }
+
     pub fn will_collide(&self, new_position: (i32, i32)) -> bool {
         self.body.contains(&new_position)
     }
 
-    fn initialize_body(length: usize) -> Vec<(i32, i32)> {
-        let mut body = Vec::new();
-        
-        let start_x = 10;
-        let start_y = 10;
-    
-        for i in 0..length {
-            body.push((start_x - i as i32, start_y));
-        }
+    fn initialize_body(&self) -> Vec<(i32, i32)> {
+        let (start_x, start_y) = self.spawn_position;
+        let (dx, dy) = self.spawn_direction.opposite().offset();
 
-        body
-    }
-
-    pub fn as_vertices(&self) -> Vec<f32> {
-        
-        let mut all_vertices = Vec::new();
-
-        for &(x, y) in &self.body {
-            let x1 = x as f32 * self.cell_size - 1.0 + self.spacing;
-            let y1 = y as f32 * self.cell_size - 1.0 + self.spacing;
-            let x2 = x1 + self.cell_size - self.spacing;
-            let y2 = y1 + self.cell_size - self.spacing * 1.4;
-
-            let vertices: [f32; 36] = [
-                x1, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-                x1, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-                x2, y1, self.color[0], self.color[1], self.color[2], self.color[3],
-                x2, y2, self.color[0], self.color[1], self.color[2], self.color[3],
-            ];
-    
-            all_vertices.extend_from_slice(&vertices);
-        }
-    
-        all_vertices
+        (0..self.body_length as i32)
+            .map(|i| (start_x + dx * i, start_y + dy * i))
+            .collect()
+    }
+
+    // Where each segment is drawn, `alpha` of the way from its previous cell to its current one.
+    // A move across a wrapping edge slides out on one side and in on the other, so it adds
+    // a second position for the part coming in rather than stretching across the board.
+    pub fn interpolated_cells(&self, alpha: f32) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.body.iter().enumerate().flat_map(move |(index, &(x, y))| {
+            // Segments added by growing have no previous cell of their own and come out of the tail.
+            let (previous_x, previous_y) = self.previous_body.get(index)
+                .or(self.previous_body.last())
+                .copied()
+                .unwrap_or((x, y));
+            let (dx, dy) = (x - previous_x, y - previous_y);
+
+            if dx.abs() > 1 || dy.abs() > 1 {
+                let (step_x, step_y) = (wrapped_step(dx) as f32, wrapped_step(dy) as f32);
+                [
+                    Some((previous_x as f32 + step_x * alpha, previous_y as f32 + step_y * alpha)),
+                    Some((x as f32 - step_x * (1.0 - alpha), y as f32 - step_y * (1.0 - alpha))),
+                ]
+            }
+            else {
+                [Some((previous_x as f32 + dx as f32 * alpha, previous_y as f32 + dy as f32 * alpha)), None]
+            }
+        })
+        .flatten()
+    }
+}
+
+// A jump of more than one cell is a single step the other way across the edge.
+fn wrapped_step(delta: i32) -> i32 {
+    match delta {
+        delta if delta > 1 => -1,
+        delta if delta < -1 => 1,
+        delta => delta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::snake_at;
+
+    #[test]
+    fn slides_between_cells() {
+        let mut snake = snake_at((5, 5), 3);
+        assert_eq!(snake.interpolated_cells(0.5).collect::<Vec<_>>(), [(5.0, 5.0), (4.0, 5.0), (3.0, 5.0)], "A new snake stands still");
+
+        snake.move_to((5, 6));
+        snake.grow();
+
+        assert_eq!(snake.interpolated_cells(0.0).collect::<Vec<_>>(), [(5.0, 5.0), (4.0, 5.0), (3.0, 5.0), (3.0, 5.0)]);
+        assert_eq!(snake.interpolated_cells(0.5).collect::<Vec<_>>(), [(5.0, 5.5), (4.5, 5.0), (3.5, 5.0), (3.5, 5.0)], "The grown segment comes out of the tail");
+        assert_eq!(snake.interpolated_cells(1.0).collect::<Vec<_>>(), [(5.0, 6.0), (5.0, 5.0), (4.0, 5.0), (4.0, 5.0)]);
     }
-    
-}
\ No newline at end of file
+
+    #[test]
+    fn wraps_without_stretching_across_the_board() {
+        let mut snake = snake_at((9, 2), 2);
+        snake.move_to((0, 2));
+
+        let cells: Vec<_> = snake.interpolated_cells(0.25).collect();
+
+        assert_eq!(cells, [(9.25, 2.0), (-0.75, 2.0), (8.25, 2.0)], "The head leaves on the right and comes in on the left");
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+use crate::{models::{Direction, GameStats, PlayerController}, net::PlayerSnapshot, objects::Snake};
+
+pub struct Player {
+    pub snake: Snake,
+    // The direction the snake moved in on the last tick.
+    pub direction: Direction,
+    pub controller: PlayerController,
+    turns: VecDeque<Direction>,
+    stats: GameStats,
+    alive: bool
+}
+
+impl Player {
+    pub fn new(snake: Snake, controller: PlayerController) -> Self {
+        let mut player = Player {
+            direction: snake.spawn_direction(),
+            stats: GameStats::new(snake.length(), false),
+            snake,
+            controller,
+            turns: VecDeque::new(),
+            alive: true
+        };
+
+        player.set_ai_playing(false);
+        player
+    }
+
+    pub fn reset(&mut self) {
+        self.direction = self.snake.spawn_direction();
+        self.snake.reset();
+        self.turns.clear();
+        self.stats = GameStats::new(self.snake.length(), self.stats.is_ai);
+        self.alive = true;
+    }
+
+    // Queues a turn to be taken on a coming tick, one per tick, keeping at most `depth` of them.
+    // Each turn is checked against the one before it, so no sequence of turns can reverse the snake.
+    // Returns whether the turn was queued.
+    pub fn queue_turn(&mut self, direction: Direction, depth: usize) -> bool {
+        let heading = self.heading();
+
+        if heading == direction || heading.opposite() == direction || self.turns.len() >= depth.max(1) {
+            return false;
+        }
+
+        self.turns.push_back(direction);
+        true
+    }
+
+    // Where the snake will be going once every queued turn has been taken.
+    pub fn heading(&self) -> Direction {
+        self.turns.back().copied().unwrap_or(self.direction)
+    }
+
+    // Takes the next queued turn, if any, and returns the direction to move in this tick.
+    pub fn next_direction(&mut self) -> Direction {
+        if let Some(direction) = self.turns.pop_front() {
+            self.direction = direction;
+        }
+
+        self.direction
+    }
+
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    pub fn stats_mut(&mut self) -> &mut GameStats {
+        &mut self.stats
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn kill(&mut self) {
+        self.alive = false;
+    }
+
+    // Players steered by an AI count as AI players even when the game is not in demo mode.
+    pub fn set_ai_playing(&mut self, is_ai: bool) {
+        self.stats.is_ai = is_ai || !self.is_human();
+    }
+
+    pub fn is_human(&self) -> bool {
+        self.controller.key_bindings().is_some()
+    }
+
+    pub fn snapshot(&self) -> PlayerSnapshot {
+        PlayerSnapshot {
+            body: self.snake.body().to_vec(),
+            direction: self.direction,
+            turns: self.turns.iter().copied().collect(),
+            alive: self.alive,
+            stats: self.stats.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &PlayerSnapshot) {
+        self.snake.set_body(snapshot.body.clone());
+        self.direction = snapshot.direction;
+        self.turns = snapshot.turns.iter().copied().collect();
+        self.alive = snapshot.alive;
+        self.stats = snapshot.stats.clone();
+    }
+}
@@ -1,12 +1,23 @@
-mod js;
 mod os;
+mod seeded;
 
-pub use js::JsRandomizer;
+pub use seeded::SeededRandomizer;
+
+use crate::models::GridSize;
 
 #[cfg(test)]
 pub use os::tests::{MockRandomizer, OsRandomizer};
 
 pub trait Randomizer {
     fn get_random_color(&mut self) -> [f32; 4];
-    fn get_random_position_on_grid(&mut self, grid_size: i32) -> (i32, i32);
-}
\ No newline at end of file
+    fn get_random_position_on_grid(&mut self, grid: GridSize) -> (i32, i32);
+    // A number in `0..bound`.
+    fn get_random_index(&mut self, bound: usize) -> usize;
+    fn reseed(&mut self, seed: u64);
+    fn next_seed(&mut self) -> u64;
+
+    // Where the sequence currently is, for randomizers that `reseed` can put back there.
+    fn state(&self) -> Option<u64> {
+        None
+    }
+}
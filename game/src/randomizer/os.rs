@@ -1,14 +1,22 @@
 #[cfg(test)]
 pub mod tests {
-    use crate::{constants::COLORS, randomizer::Randomizer};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{constants::COLORS, models::GridSize, randomizer::Randomizer};
 
     pub struct OsRandomizer {
-        rng: rand::rngs::ThreadRng,
+        rng: StdRng,
     }
     
     impl OsRandomizer {
         pub fn new() -> Self {
-            Self { rng: rand::rng() }
+            Self { rng: StdRng::from_rng(&mut rand::rng()) }
+        }
+    }
+
+    impl Default for OsRandomizer {
+        fn default() -> Self {
+            Self::new()
         }
     }
     
@@ -19,21 +27,39 @@ pub mod tests {
             *COLORS.choose(&mut self.rng).unwrap()
         }
     
-        fn get_random_position_on_grid(&mut self, grid_size: i32) -> (i32, i32) {
+        fn get_random_position_on_grid(&mut self, grid: GridSize) -> (i32, i32) {
             use rand::Rng;
     
-            let x = self.rng.random_range(1..grid_size);
-            let y = self.rng.random_range(1..grid_size);
+            let x = self.rng.random_range(1..grid.width);
+            let y = self.rng.random_range(1..grid.height);
             (x, y)
         }
+
+        fn get_random_index(&mut self, bound: usize) -> usize {
+            use rand::Rng;
+
+            self.rng.random_range(0..bound)
+        }
+
+        fn reseed(&mut self, seed: u64) {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+
+        fn next_seed(&mut self) -> u64 {
+            use rand::Rng;
+
+            self.rng.random()
+        }
     }
     
     mockall::mock! {
         pub Randomizer {}
         impl Randomizer for Randomizer {
             fn get_random_color(&mut self) -> [f32; 4];
-            fn get_random_position_on_grid(&mut self, grid_size: i32) -> (i32, i32);
+            fn get_random_position_on_grid(&mut self, grid: GridSize) -> (i32, i32);
+            fn get_random_index(&mut self, bound: usize) -> usize;
+            fn reseed(&mut self, seed: u64);
+            fn next_seed(&mut self) -> u64;
         }
     }
 }
-
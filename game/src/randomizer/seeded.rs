@@ -0,0 +1,107 @@
+use crate::{constants::COLORS, models::GridSize};
+
+use super::Randomizer;
+
+// SplitMix64 generator. Small, fast and fully determined by its seed,
+// so a game started from the same seed always plays out the same way.
+pub struct SeededRandomizer {
+    state: u64,
+}
+
+impl SeededRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+impl Randomizer for SeededRandomizer {
+    fn get_random_color(&mut self) -> [f32; 4] {
+        let index = self.next_below(COLORS.len() as u64) as usize;
+        COLORS[index]
+    }
+
+    fn get_random_position_on_grid(&mut self, grid: GridSize) -> (i32, i32) {
+        let x = self.next_below(grid.width as u64) as i32;
+        let y = self.next_below(grid.height as u64) as i32;
+        (x, y)
+    }
+
+    fn get_random_index(&mut self, bound: usize) -> usize {
+        self.next_below(bound as u64) as usize
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    // Seeds travel through JS numbers in options and replays, so keep them
+    // within the 53 bits a double can represent exactly.
+    fn next_seed(&mut self) -> u64 {
+        self.next_u64() >> 11
+    }
+
+    // The seed is the whole state, so reseeding with this continues the sequence.
+    fn state(&self) -> Option<u64> {
+        Some(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_produces_same_sequence() {
+        let mut first = SeededRandomizer::new(42);
+        let mut second = SeededRandomizer::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(first.get_random_position_on_grid(GridSize::square(30)), second.get_random_position_on_grid(GridSize::square(30)));
+            assert_eq!(first.get_random_color(), second.get_random_color());
+        }
+    }
+
+    #[test]
+    fn reseeding_with_the_state_continues_the_sequence() {
+        let mut randomizer = SeededRandomizer::new(5);
+        randomizer.get_random_position_on_grid(GridSize::square(20));
+
+        let mut resumed = SeededRandomizer::new(0);
+        resumed.reseed(randomizer.state().unwrap());
+
+        assert_eq!(resumed.get_random_position_on_grid(GridSize::square(20)), randomizer.get_random_position_on_grid(GridSize::square(20)));
+    }
+
+    #[test]
+    fn reseed_restarts_sequence() {
+        let mut randomizer = SeededRandomizer::new(7);
+        let expected: Vec<_> = (0..10).map(|_| randomizer.get_random_position_on_grid(GridSize::square(20))).collect();
+
+        randomizer.reseed(7);
+        let actual: Vec<_> = (0..10).map(|_| randomizer.get_random_position_on_grid(GridSize::square(20))).collect();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn positions_stay_on_grid() {
+        let mut randomizer = SeededRandomizer::new(1);
+
+        for _ in 0..1000 {
+            let grid = GridSize::new(5, 3);
+            assert!(grid.contains(randomizer.get_random_position_on_grid(grid)));
+        }
+    }
+}
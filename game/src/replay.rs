@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{game::Game, map::GameMap, models::{Direction, GameOptions}, randomizer::Randomizer};
+
+pub const REPLAY_VERSION: u32 = 2;
+
+// Tick, direction and the player who turned. Single player replays may leave out the player.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayInput(pub u32, pub Direction, #[serde(default)] pub usize);
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub options: GameOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<GameMap>,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, options: GameOptions, map: Option<GameMap>) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            options,
+            map,
+            inputs: vec![],
+        }
+    }
+
+    pub fn record(&mut self, tick: u32, player: usize, direction: Direction) {
+        self.inputs.push(ReplayInput(tick, direction, player));
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.version == REPLAY_VERSION
+    }
+
+    pub fn playback_options(&self) -> GameOptions {
+        GameOptions {
+            seed: Some(self.seed),
+            ..self.options.clone()
+        }
+    }
+}
+
+pub struct ReplayPlayer {
+    inputs: VecDeque<ReplayInput>,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: &Replay) -> Self {
+        Self {
+            inputs: replay.inputs.iter().copied().collect(),
+        }
+    }
+
+    pub fn apply<R: Randomizer>(&mut self, game: &mut Game<R>) {
+        while let Some(&ReplayInput(tick, direction, player)) = self.inputs.front() {
+            if tick > game.tick() {
+                break;
+            }
+
+            game.change_player_direction(player, direction);
+            self.inputs.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::{Difficulty, GameResult}, randomizer::SeededRandomizer};
+
+    fn options() -> GameOptions {
+        GameOptions {
+            difficulty: Difficulty::Hard,
+            ..GameOptions::test_default()
+        }
+    }
+
+    #[test]
+    fn replay_reproduces_run() {
+        let turns = [(3, Direction::Up), (7, Direction::Left), (7, Direction::Down), (12, Direction::Right)];
+
+        let mut game = Game::new(options(), SeededRandomizer::new(99));
+        game.initialize().unwrap();
+        game.reset();
+        let mut replay = Replay::new(game.seed(), options(), None);
+        let mut recorded_heads = vec![];
+
+        for _ in 0..30 {
+            let current_tick = game.tick();
+
+            for &(tick, direction) in turns.iter().filter(|(tick, _)| *tick == current_tick) {
+                replay.record(tick, 0, direction);
+                game.change_direction(direction);
+            }
+
+            let result = game.update();
+            recorded_heads.push(game.players[0].snake.get_head_position());
+
+            if result == GameResult::Over {
+                break;
+            }
+        }
+
+        let mut replayed = Game::new(replay.playback_options(), SeededRandomizer::new(1));
+        replayed.initialize().unwrap();
+        replayed.reset();
+        let mut player = ReplayPlayer::new(&replay);
+        let mut replayed_heads = vec![];
+
+        for _ in 0..recorded_heads.len() {
+            player.apply(&mut replayed);
+            replayed.update();
+            replayed_heads.push(replayed.players[0].snake.get_head_position());
+        }
+
+        assert_eq!(recorded_heads, replayed_heads);
+    }
+
+    #[test]
+    fn reads_inputs_without_a_player() {
+        let input: ReplayInput = serde_json::from_str(r#"[4, "up"]"#).unwrap();
+        assert_eq!(input, ReplayInput(4, Direction::Up, 0));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut replay = Replay::new(1, options(), None);
+        assert!(replay.is_supported());
+
+        replay.version = REPLAY_VERSION + 1;
+        assert!(!replay.is_supported());
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{map::GameMap, models::{GameOptions, GameState}, net::GameSnapshot};
+
+pub const SAVE_VERSION: u32 = 2;
+
+// A game in progress, with everything needed to carry on exactly where it was left.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedGame {
+    pub version: u32,
+    pub options: GameOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<GameMap>,
+    pub seed: u64,
+    // Without it the food that spawns after loading differs from the food the saved game would have had.
+    #[serde(default, with = "state_as_string")]
+    pub randomizer: Option<u64>,
+    pub snapshot: GameSnapshot,
+    // Who plays on once the game is loaded. Saves from before it was kept go back to the user.
+    #[serde(default = "default_state")]
+    pub state: GameState,
+}
+
+fn default_state() -> GameState {
+    GameState::UserPlaying
+}
+
+impl SavedGame {
+    pub fn is_supported(&self) -> bool {
+        self.version == SAVE_VERSION
+    }
+
+    // Only someone playing can carry on, a replay's inputs are not part of the save.
+    pub fn can_play_on(&self) -> bool {
+        matches!(self.state, GameState::UserPlaying | GameState::AiPlaying)
+    }
+}
+
+// Randomizer states use all 64 bits, more than a JS number holds exactly, so they are saved as strings.
+mod state_as_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(state: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        state.map(|state| state.to_string()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|state| state.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,22 @@
+// Builders shared by the unit tests of several modules.
+
+use crate::{models::Direction, objects::Snake};
+
+// A straight snake heading right, with its head at `position`.
+pub(crate) fn snake_at(position: (i32, i32), length: usize) -> Snake {
+    let mut snake = Snake::new();
+    snake.set_spawn(position, Direction::Right, length);
+    snake.initialize(length);
+    snake
+}
+
+// Head at (3, 1) with the tail right above it at (3, 2).
+pub(crate) fn coiled_snake() -> Snake {
+    let mut snake = Snake::new();
+    snake.set_spawn((3, 2), Direction::Left, 4);
+    snake.initialize(4);
+    snake.move_to((2, 2));
+    snake.move_to((2, 1));
+    snake.move_to((3, 1));
+    snake
+}
@@ -0,0 +1,101 @@
+// A throttled background tab can go seconds between frames. Catching all of that up at once
+// would play out the game before anyone could react, so only this many ticks are made up.
+pub const MAX_CATCH_UP_STEPS: u32 = 5;
+
+// Turns the time between animation frames into fixed simulation steps. Time that does not
+// make up a whole step carries over to the next frame, so ticks follow the clock and not the display.
+#[derive(Debug, Default)]
+pub struct FixedTimestep {
+    // None until the first frame after starting or resuming, which only sets the clock.
+    last_timestamp: Option<f64>,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starts over, for a new game: no clock and no time gathered towards a step.
+    pub fn reset(&mut self) {
+        self.last_timestamp = None;
+        self.accumulator = 0.0;
+    }
+
+    // Forgets the clock, so the time until the next frame does not count. The part of a step
+    // gathered so far is kept, so a resumed game is drawn from where it stopped between ticks.
+    pub fn resume(&mut self) {
+        self.last_timestamp = None;
+    }
+
+    // Adds the time since the last frame, keeping at most `max_steps` steps of it.
+    pub fn advance(&mut self, timestamp: f64, step_ms: f64, max_steps: u32) {
+        let last_timestamp = self.last_timestamp.replace(timestamp).unwrap_or(timestamp);
+        let elapsed = (timestamp - last_timestamp).max(0.0);
+
+        self.accumulator = (self.accumulator + elapsed).min(step_ms * max_steps as f64);
+    }
+
+    // Takes a step out of the time gathered so far. Returns false once less than a step is left.
+    pub fn step(&mut self, step_ms: f64) -> bool {
+        if self.accumulator < step_ms {
+            return false;
+        }
+
+        self.accumulator -= step_ms;
+        true
+    }
+
+    // How far the time left over has got towards the next step, from 0 to 1.
+    pub fn alpha(&self, step_ms: f64) -> f64 {
+        (self.accumulator / step_ms).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(timestep: &mut FixedTimestep, timestamp: f64, step_ms: f64) -> u32 {
+        timestep.advance(timestamp, step_ms, MAX_CATCH_UP_STEPS);
+        let mut count = 0;
+
+        while timestep.step(step_ms) {
+            count += 1;
+        }
+
+        count
+    }
+
+    #[test]
+    fn ticks_at_the_step_rate_whatever_the_frame_rate() {
+        let mut slow = FixedTimestep::new();
+        let mut fast = FixedTimestep::new();
+
+        // One second at 30 and at 144 frames per second, ticking every 100ms.
+        let slow_ticks: u32 = (0..=30).map(|frame| steps(&mut slow, frame as f64 * 1000.0 / 30.0, 100.0)).sum();
+        let fast_ticks: u32 = (0..=144).map(|frame| steps(&mut fast, frame as f64 * 1000.0 / 144.0, 100.0)).sum();
+
+        assert_eq!(slow_ticks, 10);
+        assert_eq!(fast_ticks, 10);
+    }
+
+    #[test]
+    fn caps_catching_up_after_a_long_gap() {
+        let mut timestep = FixedTimestep::new();
+
+        assert_eq!(steps(&mut timestep, 1000.0, 100.0), 0, "The first frame only starts the clock");
+        assert_eq!(steps(&mut timestep, 11000.0, 100.0), MAX_CATCH_UP_STEPS);
+        assert_eq!(steps(&mut timestep, 11050.0, 100.0), 0, "The dropped time should not come back later");
+
+        assert_eq!(timestep.alpha(100.0), 0.5);
+
+        timestep.resume();
+        assert_eq!(steps(&mut timestep, 20000.0, 100.0), 0, "A resumed clock starts over");
+        assert_eq!(timestep.alpha(100.0), 0.5, "Resuming keeps the time between ticks");
+
+        timestep.reset();
+        assert_eq!(steps(&mut timestep, 30000.0, 100.0), 0, "A reset clock starts over");
+        assert_eq!(timestep.alpha(100.0), 0.0, "Resetting drops the time between ticks");
+    }
+}
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+use wasm_bindgen::JsValue;
 use web_sys::{WebGlProgram, WebGl2RenderingContext, WebGlShader};
 
-use crate::{constants::{FS_SOURCE, VS_SOURCE}, models::Direction};
+use crate::{error::GameError, models::{Direction, KeyBindings}};
 
-pub fn create_shader(context: &WebGl2RenderingContext, shader_type: u32, source: &str) -> Option<WebGlShader> {
-    let shader = context.create_shader(shader_type).unwrap();
+pub fn create_shader(context: &WebGl2RenderingContext, shader_type: u32, source: &str) -> Result<WebGlShader, GameError> {
+    let shader = context.create_shader(shader_type)
+        .ok_or_else(|| GameError::ShaderCompile("could not create a shader".into()))?;
 
     context.shader_source(&shader, source);
     context.compile_shader(&shader);
@@ -13,19 +16,20 @@ pub fn create_shader(context: &WebGl2RenderingContext, shader_type: u32, source:
     let is_success = context.get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS);
 
     if !is_success.as_bool().unwrap_or_default() {
-        context.get_shader_info_log(&shader);
+        let log = context.get_shader_info_log(&shader).unwrap_or_default();
         context.delete_shader(Some(&shader));
-        return None
+        return Err(GameError::ShaderCompile(log))
     }
 
-    Some(shader)
+    Ok(shader)
 }
 
 pub fn create_program(
     context: &WebGl2RenderingContext,
     vertex_shader: WebGlShader,
-    fragment_shader: WebGlShader) -> Option<WebGlProgram> {
-    let program = context.create_program().unwrap();
+    fragment_shader: WebGlShader) -> Result<WebGlProgram, GameError> {
+    let program = context.create_program()
+        .ok_or_else(|| GameError::ProgramLink("could not create a program".into()))?;
 
     context.attach_shader(&program, &vertex_shader);
     context.attach_shader(&program, &fragment_shader);
@@ -34,27 +38,40 @@ pub fn create_program(
     let is_success = context.get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS);
 
     if !is_success.as_bool().unwrap_or_default() {
-        context.get_program_info_log(&program);
+        let log = context.get_program_info_log(&program).unwrap_or_default();
         context.delete_program(Some(&program));
-        return None
+        return Err(GameError::ProgramLink(log))
     }
 
-    Some(program)
+    Ok(program)
 }
 
-pub fn create_key_direction_map() -> HashMap<String, Direction> {
-    let key_direction_pairs = vec![
+pub fn create_key_direction_map(bindings: KeyBindings) -> HashMap<String, Direction> {
+    let wasd = [
         ("a", Direction::Left),
-        ("arrowleft", Direction::Left),
         ("w", Direction::Up),
-        ("arrowup", Direction::Up),
         ("s", Direction::Down),
-        ("arrowdown", Direction::Down),
         ("d", Direction::Right),
+    ];
+    let arrows = [
+        ("arrowleft", Direction::Left),
+        ("arrowup", Direction::Up),
+        ("arrowdown", Direction::Down),
         ("arrowright", Direction::Right),
     ];
 
+    let key_direction_pairs = match bindings {
+        KeyBindings::All => [wasd, arrows].concat(),
+        KeyBindings::Wasd => wasd.to_vec(),
+        KeyBindings::Arrows => arrows.to_vec(),
+    };
+
     key_direction_pairs.into_iter().map(|(key, direction)| {
         (key.to_string(), direction)
     }).collect()
+}
+
+// Maps become plain objects so payloads can be used directly from JS.
+pub fn to_js_value<T: Serialize>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
 }
\ No newline at end of file
@@ -0,0 +1,136 @@
+use serde::Serialize;
+
+use crate::models::GridSize;
+
+// Where the grid is drawn on the canvas. Cells stay square, so the grid is scaled to the largest
+// size that fits, centered, with the rest of the canvas left as a border. Each cell is a whole
+// number of device pixels so cell edges stay sharp on high density screens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridViewport {
+    // The grid in cells.
+    pub grid_width: i32,
+    pub grid_height: i32,
+    // Device pixels per CSS pixel.
+    pub pixel_ratio: f64,
+    // The canvas in CSS pixels.
+    pub width: f64,
+    pub height: f64,
+    // The grid's top left corner and size in CSS pixels, from the canvas' top left corner.
+    pub left: f64,
+    pub top: f64,
+    pub board_width: f64,
+    pub board_height: f64,
+    pub cell_size: f64,
+}
+
+impl GridViewport {
+    pub fn fit(width: f64, height: f64, pixel_ratio: f64, grid: GridSize) -> Self {
+        let pixel_ratio = if pixel_ratio.is_finite() && pixel_ratio > 0.0 { pixel_ratio } else { 1.0 };
+        let (grid_width, grid_height) = (grid.width.max(1), grid.height.max(1));
+        let (device_width, device_height) = ((width * pixel_ratio).floor(), (height * pixel_ratio).floor());
+
+        // Whole device pixels per cell, unless the canvas is too small to give every cell one.
+        let device_cell = (device_width / grid_width as f64).min(device_height / grid_height as f64).floor().max(1.0);
+        let (board_width, board_height) = (device_cell * grid_width as f64, device_cell * grid_height as f64);
+
+        Self {
+            grid_width,
+            grid_height,
+            pixel_ratio,
+            width,
+            height,
+            left: ((device_width - board_width) / 2.0).floor() / pixel_ratio,
+            top: ((device_height - board_height) / 2.0).floor() / pixel_ratio,
+            board_width: board_width / pixel_ratio,
+            board_height: board_height / pixel_ratio,
+            cell_size: device_cell / pixel_ratio,
+        }
+    }
+
+    pub fn grid(&self) -> GridSize {
+        GridSize::new(self.grid_width, self.grid_height)
+    }
+
+    // The canvas' drawing buffer size in device pixels.
+    pub fn device_size(&self) -> (u32, u32) {
+        ((self.width * self.pixel_ratio).floor() as u32, (self.height * self.pixel_ratio).floor() as u32)
+    }
+
+    // The grid's area of the drawing buffer as x, y, width and height, counted from the
+    // bottom left corner as WebGL expects.
+    pub fn device_rect(&self) -> (i32, i32, i32, i32) {
+        let (_, device_height) = self.device_size();
+        let width = (self.board_width * self.pixel_ratio).round() as i32;
+        let height = (self.board_height * self.pixel_ratio).round() as i32;
+        let x = (self.left * self.pixel_ratio).round() as i32;
+        let y = device_height as i32 - (self.top * self.pixel_ratio).round() as i32 - height;
+
+        (x, y, width, height)
+    }
+
+    // The top left corner of a cell in CSS pixels. Row 0 is at the bottom of the board.
+    pub fn cell_to_pixel(&self, (x, y): (i32, i32)) -> (f64, f64) {
+        let row_from_top = self.grid_height - 1 - y;
+        (self.left + x as f64 * self.cell_size, self.top + row_from_top as f64 * self.cell_size)
+    }
+
+    // The cell under a point in CSS pixels, if the point is on the board.
+    pub fn pixel_to_cell(&self, (x, y): (f64, f64)) -> Option<(i32, i32)> {
+        let column = ((x - self.left) / self.cell_size).floor() as i32;
+        let row_from_top = ((y - self.top) / self.cell_size).floor() as i32;
+        let cell = (column, self.grid_height - 1 - row_from_top);
+
+        self.grid().contains(cell).then_some(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterboxes_a_wide_canvas() {
+        let viewport = GridViewport::fit(1000.0, 600.0, 1.0, GridSize::square(20));
+
+        assert_eq!((viewport.board_width, viewport.board_height), (600.0, 600.0));
+        assert_eq!(viewport.cell_size, 30.0);
+        assert_eq!((viewport.left, viewport.top), (200.0, 0.0));
+        assert_eq!(viewport.device_rect(), (200, 0, 600, 600));
+    }
+
+    #[test]
+    fn keeps_cells_on_whole_device_pixels() {
+        let viewport = GridViewport::fit(500.0, 810.0, 2.0, GridSize::square(30));
+
+        assert_eq!(viewport.device_size(), (1000, 1620));
+        assert_eq!(viewport.device_rect(), (5, 315, 990, 990), "33 device pixels per cell, centered");
+        assert_eq!(viewport.cell_size, 16.5);
+        assert_eq!((viewport.left, viewport.top), (2.5, 157.5));
+    }
+
+    #[test]
+    fn maps_cells_to_pixels_and_back() {
+        let viewport = GridViewport::fit(1000.0, 600.0, 1.5, GridSize::new(30, 12));
+
+        assert_eq!(viewport.cell_to_pixel((0, 11)), (viewport.left, viewport.top), "The top row is the last one");
+
+        for cell in [(0, 0), (29, 11), (17, 6)] {
+            let (x, y) = viewport.cell_to_pixel(cell);
+            let center = (x + viewport.cell_size / 2.0, y + viewport.cell_size / 2.0);
+            assert_eq!(viewport.pixel_to_cell(center), Some(cell));
+        }
+
+        assert_eq!(viewport.pixel_to_cell((viewport.left - 1.0, 300.0)), None, "The border is not part of the board");
+        assert_eq!(viewport.pixel_to_cell((500.0, viewport.top - 1.0)), None);
+    }
+
+    #[test]
+    fn fits_a_tall_grid_to_the_narrow_side() {
+        let viewport = GridViewport::fit(400.0, 900.0, 1.0, GridSize::new(10, 30));
+
+        assert_eq!(viewport.cell_size, 30.0, "The height limits the cells, not the width");
+        assert_eq!((viewport.board_width, viewport.board_height), (300.0, 900.0));
+        assert_eq!(viewport.device_rect(), (50, 0, 300, 900));
+    }
+}
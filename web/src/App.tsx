@@ -1,22 +1,25 @@
 import Panel from "components/Panel";
-import { useEffect, useState } from "react";
-import init, { setup, stop, play, applyOptions } from "snake-game";
+import { useEffect, useRef, useState } from "react";
+import init, { setup, stop, play, pause, resume, applyOptions, validateOptions, saveGame, loadGame, subscribe } from "snake-game";
 import Start from "components/Prompt";
-import { GameOptions, GameState } from "lib/types";
+import { FieldError, GameEvent, GameOptions, GameState, SavedGame } from "lib/types";
 
 const defaultOptions: GameOptions = {
     id: "canvas",
     snakeColor: "#FFFFFF",
     difficulty: "easy",
-    gridSize: 30,
+    gridWidth: 30,
+    gridHeight: 30,
     foodCount: 5,
     fps: 10,
-    frameThresholdMs: 1000 / 10
+    boundaryMode: "wrap",
+    aiStrategy: "greedy"
 };
 
 function getOptions() {
     const json = localStorage.getItem("settings");
-    const options = json ? JSON.parse(json) : defaultOptions;
+    // Settings saved by older versions can miss options added since.
+    const options = json ? { ...defaultOptions, ...JSON.parse(json) } : defaultOptions;
     return options;
 }
 
@@ -24,21 +27,59 @@ function saveOptionsToLocalStorage(options: GameOptions) {
     localStorage.setItem("settings", JSON.stringify(options));
 }
 
+// A game left running when the tab closed, continued on the next start.
+function takeSavedGame(): SavedGame | null {
+    const json = localStorage.getItem("savedGame");
+    localStorage.removeItem("savedGame");
+    return json ? JSON.parse(json) : null;
+}
+
+// Continues the saved game if there is one, otherwise starts a new one. Returns the starting score.
+function startGame(): number {
+    const saved = takeSavedGame();
+
+    if(saved) {
+        try {
+            loadGame(saved);
+            return saved.snapshot.players[0].stats.score;
+        }
+        catch(error) {
+            console.warn("Could not continue the saved game:", error);
+        }
+    }
+
+    play(false);
+    return 0;
+}
+
 function App() {
     const [options, setOptions] = useState(getOptions());
     const [state, setState] = useState<GameState>({ type: "loading" });
+    const [optionErrors, setOptionErrors] = useState<FieldError[]>([]);
+    const stateRef = useRef(state);
+    stateRef.current = state;
 
     useEffect(() => {
         onLoad();
 
         window.addEventListener("keydown", onKeyDown);
+        window.addEventListener("beforeunload", onBeforeUnload);
 
         return () => {
+            window.removeEventListener("beforeunload", onBeforeUnload);
             stop();
         }
 
     }, []);
 
+    function onBeforeUnload() {
+        const type = stateRef.current.type;
+
+        if(type === "playing" || type === "settings") {
+            localStorage.setItem("savedGame", JSON.stringify(saveGame()));
+        }
+    }
+
     function onKeyDown(event: KeyboardEvent) {
         
         if(event.code === "Space") {
@@ -47,11 +88,10 @@ function App() {
                     return state;
                 }
 
-                play(false);
                 return {
                     ...state,
                     type: "playing",
-                    score: 0
+                    score: startGame()
                 }
             });
         }
@@ -60,6 +100,7 @@ function App() {
     async function onLoad() {
         await init();
         setup(options, onScore, onGameOver);
+        subscribe("matchOver", onMatchOver);
         play(true);
         setState(state => {
             return {
@@ -69,21 +110,44 @@ function App() {
         });
     }
 
-    function onGameOver() {
+    function onGameOver(event: GameEvent) {
+        if(event.type !== "gameOver" || event.stats.isAi) {
+            return;
+        }
+
         setState(state => {
             return {
                 ...state,
-                type: "game-over"
+                type: "game-over",
+                score: event.stats.score
             } as GameState
         });
     }
 
-    function onScore() {
+    function onMatchOver(event: GameEvent) {
+        if(event.type !== "matchOver" || event.players[0].isAi) {
+            return;
+        }
+
+        setState(state => {
+            return {
+                ...state,
+                type: "game-over",
+                score: event.players[0].score
+            } as GameState
+        });
+    }
+
+    function onScore(event: GameEvent) {
+        if(event.type !== "foodEaten" || event.player !== 0 || event.stats.isAi) {
+            return;
+        }
+
         setState(state => {
             if(state.type === "playing") {
                 return {
                     ...state,
-                    score: state.score + 1
+                    score: event.stats.score
                 }
             }
 
@@ -94,14 +158,14 @@ function App() {
     function onToggle() {
         setState(state => {
             if(state.type === "settings") {
-                play(false);
+                resume();
                 return {
                     ...state,
                     type: "playing"
                 }
             }
 
-            stop();
+            pause();
             return {
                 ...state,
                 type: "settings"
@@ -111,6 +175,14 @@ function App() {
 
     function onOptionChange(options: GameOptions) {
         setOptions(options);
+
+        const errors: FieldError[] = validateOptions(options);
+        setOptionErrors(errors);
+
+        if(errors.length) {
+            return;
+        }
+
         saveOptionsToLocalStorage(options);
         applyOptions(options);
     }
@@ -128,6 +200,7 @@ function App() {
                         isOpen={state.type === "settings"}
                         onToggle={onToggle}
                         options={options}
+                        errors={optionErrors}
                         onOptionChange={onOptionChange}/>
                 </>
         }
@@ -8,10 +8,10 @@ describe('Panel', () => {
             id: "canvas",
             snakeColor: "#FFFFFF",
             difficulty: "hard",
-            gridSize: 30,
+            gridWidth: 30,
+            gridHeight: 30,
             foodCount: 5,
-            fps: 10,
-            frameThresholdMs: 1000 / 10
+            fps: 10
         } as const;
 
         render(<Panel
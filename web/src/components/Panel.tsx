@@ -3,7 +3,7 @@ import { FC } from "react";
 import { Ellipsis, ChevronLeft } from 'lucide-react';
 import { ColorPicker, Select, Slider } from 'antd';
 import { Github } from "components/Icons";
-import { GameOptions } from "lib/types";
+import { AiStrategy, BoundaryMode, FieldError, FoodWeights, GameOptions, Level, PlayerOptions } from "lib/types";
 import { AggregationColor } from "antd/es/color-picker/color";
 
 const difficulties = [
@@ -17,6 +17,131 @@ const difficulties = [
     }
 ];
 
+const boundaryModes = [
+    {
+        value: "wrap",
+        label: "Wrap around"
+    },
+    {
+        value: "solid",
+        label: "Solid"
+    },
+    {
+        value: "wrapHorizontal",
+        label: "Wrap left/right"
+    },
+    {
+        value: "wrapVertical",
+        label: "Wrap top/bottom"
+    }
+];
+
+const aiStrategies = [
+    {
+        value: "greedy",
+        label: "Greedy"
+    },
+    {
+        value: "aStar",
+        label: "A*"
+    },
+    {
+        value: "hamiltonian",
+        label: "Hamiltonian cycle"
+    },
+    {
+        value: "tailChasing",
+        label: "Tail chasing"
+    },
+    {
+        value: "survival",
+        label: "Survival"
+    }
+];
+
+const playerModes = [
+    {
+        value: "single",
+        label: "Single player"
+    },
+    {
+        value: "versus",
+        label: "Versus (WASD vs arrows)"
+    },
+    {
+        value: "versusAi",
+        label: "Versus AI"
+    }
+];
+
+const secondPlayerColor = "#3399FF";
+
+function getPlayerMode(options: GameOptions) {
+    const players = options.players ?? [];
+
+    if(players.length < 2) {
+        return "single";
+    }
+
+    return players[1].controller?.type === "ai" ? "versusAi" : "versus";
+}
+
+function getPlayers(mode: string, options: GameOptions): PlayerOptions[] {
+    switch(mode) {
+        case "versus":
+            return [
+                { color: options.snakeColor, controller: { type: "human", keys: "wasd" } },
+                { color: secondPlayerColor, controller: { type: "human", keys: "arrows" } }
+            ];
+        case "versusAi":
+            return [
+                { color: options.snakeColor, controller: { type: "human", keys: "all" } },
+                { color: secondPlayerColor, controller: { type: "ai", strategy: options.aiStrategy } }
+            ];
+        default:
+            return [];
+    }
+}
+
+const foodPresets: Record<string, FoodWeights> = {
+    classic: { regular: 1 },
+    special: { regular: 10, bonus: 3, golden: 1, shrink: 2, speedUp: 2, slowDown: 2, poison: 2 }
+};
+
+const foodModes = [
+    {
+        value: "classic",
+        label: "Classic"
+    },
+    {
+        value: "special",
+        label: "Special food"
+    }
+];
+
+function getFoodMode(options: GameOptions) {
+    const weights = options.foodWeights ?? foodPresets.classic;
+    return Object.keys(weights).some(kind => kind !== "regular" && weights[kind as keyof FoodWeights]) ? "special" : "classic";
+}
+
+// Every 5 points the game speeds up and another obstacle appears, until it runs twice as fast.
+const progressiveLevels: Level[] = Array.from({ length: 10 }, (_, index) => ({
+    score: (index + 1) * 5,
+    speed: 1 + (index + 1) * 0.1,
+    obstacles: 1
+}));
+
+const levelModes = [
+    {
+        value: "off",
+        label: "Off"
+    },
+    {
+        value: "progressive",
+        label: "Progressive"
+    }
+];
+
 const fpsOptions = [
     {
         value: 10,
@@ -35,6 +160,7 @@ const fpsOptions = [
 interface Props {
     isOpen: boolean;
     options: GameOptions;
+    errors: FieldError[];
     onOptionChange(options: GameOptions): void;
     onToggle(): void;
 }
@@ -42,20 +168,31 @@ interface Props {
 const Panel: FC<Props> = ({
     isOpen,
     options,
+    errors,
     onOptionChange,
     onToggle}) => {
 
-    const onGridSizeChange = (gridSize: number) => {
+    const onGridWidthChange = (gridWidth: number) => {
         onOptionChange({
             ...options,
-            gridSize
+            gridWidth
+        })
+    }
+
+    const onGridHeightChange = (gridHeight: number) => {
+        onOptionChange({
+            ...options,
+            gridHeight
         })
     }
 
     const onSnakeColorChange = (value: AggregationColor, css: string) => {
+        const snakeColor = value.toHex();
+
         onOptionChange({
             ...options,
-            snakeColor: value.toHex()
+            snakeColor,
+            players: options.players?.map((player, index) => index === 0 ? { ...player, color: snakeColor } : player)
         })
     }
 
@@ -66,11 +203,45 @@ const Panel: FC<Props> = ({
         })
     }
 
+    const onBoundaryModeChange = (boundaryMode: string) => {
+        onOptionChange({
+            ...options,
+            boundaryMode: boundaryMode as BoundaryMode
+        })
+    }
+
+    const onAiStrategyChange = (aiStrategy: string) => {
+        onOptionChange({
+            ...options,
+            aiStrategy: aiStrategy as AiStrategy
+        })
+    }
+
+    const onPlayerModeChange = (mode: string) => {
+        onOptionChange({
+            ...options,
+            players: getPlayers(mode, options)
+        })
+    }
+
+    const onFoodModeChange = (mode: string) => {
+        onOptionChange({
+            ...options,
+            foodWeights: foodPresets[mode]
+        })
+    }
+
+    const onLevelModeChange = (mode: string) => {
+        onOptionChange({
+            ...options,
+            levels: mode === "progressive" ? progressiveLevels : []
+        })
+    }
+
     const onFpsOptionChange = (fps: number) => {
         onOptionChange({
             ...options,
-            fps,
-            frameThresholdMs: 1000 / fps
+            fps
         })
     }
 
@@ -103,18 +274,55 @@ const Panel: FC<Props> = ({
                             onChange={onDifficultyChange}
                             options={difficulties}
                         />
+                        <h4 className="my-2">Walls</h4>
+                        <Select
+                            defaultValue={options.boundaryMode ?? "wrap"}
+                            onChange={onBoundaryModeChange}
+                            options={boundaryModes}
+                        />
+                        <h4 className="my-2">AI</h4>
+                        <Select
+                            defaultValue={options.aiStrategy ?? "greedy"}
+                            onChange={onAiStrategyChange}
+                            options={aiStrategies}
+                        />
+                        <h4 className="my-2">Players</h4>
+                        <Select
+                            defaultValue={getPlayerMode(options)}
+                            onChange={onPlayerModeChange}
+                            options={playerModes}
+                        />
+                        <h4 className="my-2">Food</h4>
+                        <Select
+                            defaultValue={getFoodMode(options)}
+                            onChange={onFoodModeChange}
+                            options={foodModes}
+                        />
+                        <h4 className="my-2">Levels</h4>
+                        <Select
+                            defaultValue={options.levels?.length ? "progressive" : "off"}
+                            onChange={onLevelModeChange}
+                            options={levelModes}
+                        />
                         <h4 className="my-2">Fps</h4>
                         <Select
                             defaultValue={options.fps}
                             onChange={onFpsOptionChange}
                             options={fpsOptions}
                         />
-                        <h4 className="mt-2">Grid size</h4>
+                        <h4 className="mt-2">Grid width</h4>
+                        <Slider
+                            min={10}
+                            max={60}
+                            value={options.gridWidth}
+                            onChange={onGridWidthChange}
+                            />
+                        <h4 className="mt-2">Grid height</h4>
                         <Slider
-                            min={20}
-                            max={40}
-                            value={options.gridSize}
-                            onChange={onGridSizeChange}
+                            min={10}
+                            max={60}
+                            value={options.gridHeight}
+                            onChange={onGridHeightChange}
                             />
                         <h4 className="mt-2">Food count</h4>
                         <Slider
@@ -123,6 +331,7 @@ const Panel: FC<Props> = ({
                             value={options.foodCount}
                             onChange={onFoodCountChange}
                             />
+                        {errors.map(error => <div key={error.field} className="mt-2 text-red-400">{error.message}</div>)}
                         </div>
                 </div>
                 <div className="flex justify-center items-center">
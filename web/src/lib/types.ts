@@ -1,13 +1,129 @@
+export type BoundaryMode = "wrap" | "solid" | "wrapHorizontal" | "wrapVertical";
+
+export type AiStrategy = "greedy" | "aStar" | "hamiltonian" | "tailChasing" | "survival";
+
+export type KeyBindings = "all" | "wasd" | "arrows";
+
+export type PlayerController = {
+    type: "human";
+    keys?: KeyBindings;
+} | {
+    type: "ai";
+    strategy?: AiStrategy;
+};
+
+export interface PlayerOptions {
+    color: string;
+    controller?: PlayerController;
+}
+
 export interface GameOptions {
     id: string;
     snakeColor: string;
     difficulty: "easy" | "hard",
-    gridSize: number;
+    gridWidth: number;
+    gridHeight: number;
     foodCount: number;
+    // Game ticks per second, the canvas is redrawn on every display frame.
     fps: number;
-    frameThresholdMs: number;
+    seed?: number;
+    boundaryMode?: BoundaryMode;
+    aiStrategy?: AiStrategy;
+    players?: PlayerOptions[];
+    inputBuffer?: number;
+    foodWeights?: FoodWeights;
+    levels?: Level[];
 };
 
+// A step of the difficulty curve, reached once the best score gets to `score`.
+export interface Level {
+    score: number;
+    speed?: number;
+    obstacles?: number;
+    foodCount?: number;
+}
+
+export type FoodKind = "regular" | "bonus" | "golden" | "shrink" | "speedUp" | "slowDown" | "poison";
+
+// How often each kind of food spawns relative to the others. Missing kinds never spawn,
+// except regular food, which defaults to 1.
+export type FoodWeights = Partial<Record<FoodKind, number>>;
+
+export interface GameStats {
+    score: number;
+    length: number;
+    maxLength: number;
+    ticks: number;
+    foodsEaten: Partial<Record<FoodKind, number>>;
+    isAi: boolean;
+}
+
+export type Direction = "up" | "down" | "left" | "right";
+
+export type GameOverReason = "selfCollision" | "wall" | "obstacle" | "headToHead" | "headToBody" | "poisoned";
+
+export type GameEvent = {
+    type: "foodEaten";
+    player: number;
+    kind: FoodKind;
+    position: [number, number];
+    stats: GameStats;
+} | {
+    type: "foodExpired";
+    kind: FoodKind;
+    position: [number, number];
+} | {
+    type: "directionChanged";
+    player: number;
+    direction: Direction;
+} | {
+    type: "paused" | "resumed";
+} | {
+    type: "levelUp";
+    level: number;
+} | {
+    type: "obstacleSpawned";
+    position: [number, number];
+    cells: [number, number][];
+} | {
+    type: "gameOver";
+    reason: GameOverReason;
+    stats: GameStats;
+} | {
+    type: "playerDied";
+    player: number;
+    reason: GameOverReason;
+    stats: GameStats;
+} | {
+    type: "matchOver";
+    winner: number | null;
+    players: GameStats[];
+}
+
+export interface Rect {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export type ObstacleShape = Rect | { rects: Rect[] } | { cells: [number, number][] };
+
+export interface GameMap {
+    name?: string;
+    gridWidth: number;
+    gridHeight: number;
+    boundaryMode?: BoundaryMode;
+    walls?: { from: [number, number]; to: [number, number] }[];
+    obstacles?: ObstacleShape[];
+    spawn: {
+        position: [number, number];
+        direction: Direction;
+        length?: number;
+    };
+    foodZones?: Rect[];
+}
+
 export type GameState = {
     type: "loading";
 } | {
@@ -15,4 +131,66 @@ export type GameState = {
 } | {
     type: "playing" | "settings" | "game-over";
     score: number;
-}
\ No newline at end of file
+}
+
+export type GameErrorCode =
+    | "notInitialized"
+    | "invalidOptions"
+    | "invalidColor"
+    | "invalidMap"
+    | "unreadableMap"
+    | "invalidReplay"
+    | "unsupportedReplay"
+    | "noReplay"
+    | "invalidSave"
+    | "unsupportedSave"
+    | "invalidMessage"
+    | "noWindow"
+    | "canvasNotFound"
+    | "webGl2Unavailable"
+    | "shaderCompile"
+    | "programLink"
+    | "invalidTransition"
+    | "notConnected"
+    | "callbackFailed";
+
+// What `saveGame` returns. Only the parts the app reads are spelled out.
+export interface SavedGame {
+    version: number;
+    options: GameOptions;
+    snapshot: {
+        tick: number;
+        players: { stats: GameStats }[];
+    };
+}
+
+// One problem found by `validateOptions`.
+export interface FieldError {
+    field: string;
+    message: string;
+}
+
+// What the game's exports throw when they fail.
+export interface GameError extends Error {
+    name: "GameError";
+    code: GameErrorCode;
+}
+
+// What `getViewport` returns: where the grid is drawn on the canvas, in CSS pixels.
+export interface GridViewport {
+    gridWidth: number;
+    gridHeight: number;
+    pixelRatio: number;
+    width: number;
+    height: number;
+    left: number;
+    top: number;
+    boardWidth: number;
+    boardHeight: number;
+    cellSize: number;
+}
+
+// The top left corner of a cell in CSS pixels, for lining overlays up with the board. Row 0 is at the bottom.
+export function cellToPixel(viewport: GridViewport, [x, y]: [number, number]): [number, number] {
+    return [viewport.left + x * viewport.cellSize, viewport.top + (viewport.gridHeight - 1 - y) * viewport.cellSize];
+}